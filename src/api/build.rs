@@ -0,0 +1,32 @@
+use std::env;
+use std::process::Command;
+
+/// Embeds the git commit into the binary. `SORUME_GIT_COMMIT` can be set when building outside of
+/// a git checkout, e.g. in a container.
+fn main() {
+    println!("cargo:rerun-if-env-changed=SORUME_GIT_COMMIT");
+
+    let git_commit = env::var("SORUME_GIT_COMMIT").ok().or_else(|| {
+        let git_dir = git(&["rev-parse", "--git-dir"])?;
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+        println!("cargo:rerun-if-changed={}/refs", git_dir);
+
+        git(&["rev-parse", "HEAD"])
+    });
+
+    if let Some(git_commit) = git_commit {
+        println!("cargo:rustc-env=SORUME_GIT_COMMIT={}", git_commit);
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|output| output.trim().to_owned())
+}
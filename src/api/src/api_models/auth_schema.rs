@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PostLogin {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetSession {
+    pub access_token: String,
+    pub token_type: String,
+    pub expiration_date: NaiveDateTime,
+}
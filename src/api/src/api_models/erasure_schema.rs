@@ -0,0 +1,36 @@
+use entities::erasure_audit;
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetErasureAudit {
+    pub erasure_audit_id: i32,
+    pub profile_id: i32,
+    pub requested_by: i32,
+    /// Either `anonymize` or `delete`
+    pub policy: String,
+    pub erased_at: NaiveDateTime,
+    pub removed_memberships: i64,
+    /// Group messages which were anonymized or deleted
+    pub affected_group_messages: i64,
+    /// Private messages which were anonymized or deleted
+    pub affected_private_messages: i64,
+    pub revoked_sessions: i64,
+}
+
+impl From<erasure_audit::Model> for GetErasureAudit {
+    fn from(audit_entry: erasure_audit::Model) -> Self {
+        GetErasureAudit {
+            erasure_audit_id: audit_entry.erasure_audit_id,
+            profile_id: audit_entry.profile_id,
+            requested_by: audit_entry.requested_by,
+            policy: audit_entry.policy,
+            erased_at: audit_entry.erased_at,
+            removed_memberships: audit_entry.removed_memberships,
+            affected_group_messages: audit_entry.affected_group_messages,
+            affected_private_messages: audit_entry.affected_private_messages,
+            revoked_sessions: audit_entry.revoked_sessions,
+        }
+    }
+}
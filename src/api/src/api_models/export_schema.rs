@@ -0,0 +1,65 @@
+use crate::api_models::group_chat_message_schema::GetGroupChatMessage;
+use crate::api_models::private_chat_schema::GetPrivateChat;
+use crate::api_models::private_message_schema::GetPrivateMessage;
+use entities::{group_chat, profile};
+use serde::Serialize;
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+/// Profile as stored, except for the password hash
+#[derive(Serialize, ToSchema)]
+pub struct ExportProfile {
+    pub profile_id: i32,
+    pub username: String,
+    pub displayname: Option<String>,
+    pub email_address: String,
+    pub join_datetime: NaiveDateTime,
+    pub profile_picture: Option<String>,
+    pub is_admin: bool,
+}
+
+impl From<profile::Model> for ExportProfile {
+    fn from(profile: profile::Model) -> Self {
+        ExportProfile {
+            profile_id: profile.profile_id,
+            username: profile.username,
+            displayname: profile.displayname,
+            email_address: profile.email_address,
+            join_datetime: profile.join_datetime,
+            profile_picture: profile.profile_picture,
+            is_admin: profile.is_admin,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExportGroupChatMembership {
+    pub group_chat_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub creation_date: NaiveDateTime,
+    pub group_picture: Option<String>,
+}
+
+impl From<group_chat::Model> for ExportGroupChatMembership {
+    fn from(group_chat: group_chat::Model) -> Self {
+        ExportGroupChatMembership {
+            group_chat_id: group_chat.group_chat_id,
+            name: group_chat.name,
+            description: group_chat.description,
+            creation_date: group_chat.creation_date,
+            group_picture: group_chat.group_picture,
+        }
+    }
+}
+
+/// Single line of a personal data export
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ExportRecord {
+    Profile(ExportProfile),
+    GroupChatMembership(ExportGroupChatMembership),
+    GroupChatMessage(GetGroupChatMessage),
+    PrivateChat(GetPrivateChat),
+    PrivateMessage(GetPrivateMessage),
+}
@@ -0,0 +1,66 @@
+use crate::api_models::group_chat_join_request_schema::GetGroupChatJoinRequest;
+use crate::api_models::group_chat_member_schema::MemberRole;
+use crate::api_models::group_chat_message_schema::GetGroupChatMessage;
+use crate::api_models::private_message_schema::GetPrivateMessage;
+use crate::api_models::read_state_schema::GetReadState;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Deserialize, IntoParams)]
+pub struct GatewayQuery {
+    /// Access token, for clients which cannot set the `Authorization` header
+    pub access_token: Option<String>,
+}
+
+/// Event pushed to the subscribed clients of the WebSocket gateway
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum GatewayEvent {
+    PrivateMessageCreated(GetPrivateMessage),
+    PrivateMessageUpdated(GetPrivateMessage),
+    PrivateMessageDeleted {
+        private_message_id: i32,
+    },
+    PrivateChatDeleted {
+        sender_id: i32,
+        recipient_id: i32,
+    },
+    PrivateChatRead(GetReadState),
+    GroupChatCreated {
+        group_chat_id: i32,
+    },
+    GroupChatDeleted {
+        group_chat_id: i32,
+    },
+    GroupChatMessageCreated(GetGroupChatMessage),
+    GroupChatMessageUpdated(GetGroupChatMessage),
+    GroupChatMessageDeleted {
+        group_chat_id: i32,
+        message_id: i32,
+    },
+    GroupChatRead(GetReadState),
+    GroupChatMessagesDeleted {
+        group_chat_id: i32,
+        author_id: Option<i32>,
+    },
+    GroupChatMemberAdded {
+        group_chat_id: i32,
+        profile_id: i32,
+    },
+    GroupChatMemberRemoved {
+        group_chat_id: i32,
+        profile_id: i32,
+    },
+    GroupChatMemberRoleChanged {
+        group_chat_id: i32,
+        profile_id: i32,
+        role: MemberRole,
+    },
+    /// Only sent to the admins and the owner of the group chat
+    GroupChatJoinRequested(GetGroupChatJoinRequest),
+    /// The request has been rejected or withdrawn, accepted requests lead to a new member instead
+    GroupChatJoinRequestDeleted {
+        group_chat_id: i32,
+        profile_id: i32,
+    },
+}
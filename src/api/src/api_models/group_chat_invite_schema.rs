@@ -0,0 +1,44 @@
+use entities::group_chat_invite;
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PostGroupChatInvite {
+    /// Invites without an expiry stay usable until they are revoked
+    pub expires_at: Option<NaiveDateTime>,
+    /// At least 1, invites without a limit may be used any number of times
+    pub max_uses: Option<i32>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetGroupChatInvite {
+    pub invite_id: i32,
+    pub group_chat_id: i32,
+    /// Shared with the invitees, who join using `POST /invite/{code}/join`
+    pub code: String,
+    /// Empty if the creator has been erased
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub max_uses: Option<i32>,
+    /// Number of joins and join requests made with this invite
+    pub uses: i32,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+impl From<group_chat_invite::Model> for GetGroupChatInvite {
+    fn from(invite: group_chat_invite::Model) -> Self {
+        GetGroupChatInvite {
+            invite_id: invite.invite_id,
+            group_chat_id: invite.group_chat_id,
+            code: invite.code,
+            created_by: invite.created_by,
+            created_at: invite.created_at,
+            expires_at: invite.expires_at,
+            max_uses: invite.max_uses,
+            uses: invite.uses,
+            revoked_at: invite.revoked_at,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use entities::group_chat_join_request;
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetGroupChatJoinRequest {
+    pub group_chat_id: i32,
+    pub profile_id: i32,
+    /// Invite which has been used for the request
+    pub invite_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<group_chat_join_request::Model> for GetGroupChatJoinRequest {
+    fn from(join_request: group_chat_join_request::Model) -> Self {
+        GetGroupChatJoinRequest {
+            group_chat_id: join_request.group_chat_id,
+            profile_id: join_request.profile_id,
+            invite_id: join_request.invite_id,
+            created_at: join_request.created_at,
+        }
+    }
+}
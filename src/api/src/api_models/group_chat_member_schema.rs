@@ -1,6 +1,37 @@
+use database::GroupChatRole;
+use entities::group_chat_member;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// Role of a member, see the permission matrix in the README
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+impl From<GroupChatRole> for MemberRole {
+    fn from(role: GroupChatRole) -> Self {
+        match role {
+            GroupChatRole::Owner => MemberRole::Owner,
+            GroupChatRole::Admin => MemberRole::Admin,
+            GroupChatRole::Member => MemberRole::Member,
+        }
+    }
+}
+
+impl From<MemberRole> for GroupChatRole {
+    fn from(role: MemberRole) -> Self {
+        match role {
+            MemberRole::Owner => GroupChatRole::Owner,
+            MemberRole::Admin => GroupChatRole::Admin,
+            MemberRole::Member => GroupChatRole::Member,
+        }
+    }
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct PostGroupChatMember {
     pub profile_id: i32,
@@ -9,4 +40,20 @@ pub struct PostGroupChatMember {
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetGroupChatMember {
     pub profile_id: i32,
+    pub role: MemberRole,
+}
+
+impl From<group_chat_member::Model> for GetGroupChatMember {
+    fn from(membership: group_chat_member::Model) -> Self {
+        GetGroupChatMember {
+            profile_id: membership.profile_id,
+            role: GroupChatRole::of(&membership).into(),
+        }
+    }
+}
+
+/// Making a member the owner transfers the ownership, the previous owner becomes an admin
+#[derive(Deserialize, ToSchema)]
+pub struct PatchGroupChatMember {
+    pub role: MemberRole,
 }
@@ -1,20 +1,54 @@
+use entities::{group_chat_member, group_chat_message};
 use serde::{Deserialize, Serialize};
 use serde_with::chrono::NaiveDateTime;
 use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
 pub struct PostGroupChatMessage {
-    pub author_id: i32,
     pub content: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetGroupChatMessage {
     pub message_id: i32,
-    pub author_id: i32,
+    /// Empty if the author has been erased
+    pub author_id: Option<i32>,
     pub send_time: NaiveDateTime,
     pub content: Option<String>,
     pub chat_id: i32,
+    /// Number of other members who have read the message
+    pub seen_by: u64,
+}
+
+impl GetGroupChatMessage {
+    /// Counts the members, except for the author, whose read cursor has reached the message.
+    pub fn with_read_state(mut self, members: &[group_chat_member::Model]) -> Self {
+        self.seen_by = members
+            .iter()
+            .filter(|member| Some(member.profile_id) != self.author_id)
+            .filter(|member| member.last_read_message_id >= Some(self.message_id))
+            .count() as u64;
+        self
+    }
+}
+
+impl From<group_chat_message::Model> for GetGroupChatMessage {
+    fn from(message: group_chat_message::Model) -> Self {
+        GetGroupChatMessage {
+            message_id: message.message_id,
+            author_id: message.author_id,
+            send_time: message.send_time,
+            content: message.content,
+            chat_id: message.chat_id,
+            seen_by: 0,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetGroupChatMessagePage {
+    pub messages: Vec<GetGroupChatMessage>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -1,3 +1,4 @@
+use crate::api_models::group_chat_member_schema::GetGroupChatMember;
 use serde::{Deserialize, Serialize};
 use serde_with::chrono::NaiveDateTime;
 use utoipa::ToSchema;
@@ -5,17 +6,43 @@ use utoipa::ToSchema;
 // TODO: group picture
 #[derive(Deserialize, ToSchema)]
 pub struct PostGroupChat {
+    /// Between 1 and 64 characters, surrounding whitespace is removed
+    pub name: String,
+    /// At most 1024 characters, surrounding whitespace is removed
+    pub description: Option<String>,
     pub member_ids: Vec<i32>,
     pub group_picture: Option<String>,
 }
 
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetNewGroupChat {
+    pub group_chat_id: i32,
+    /// Member IDs which were skipped, because no such profile exists
+    pub rejected_member_ids: Vec<i32>,
+}
+
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetGroupChat {
+    pub group_chat_id: i32,
+    /// Empty for group chats which were created before names were introduced
+    pub name: String,
+    pub description: Option<String>,
+    /// Empty if the creator has been erased
+    pub created_by: Option<i32>,
     pub creation_date: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
     pub group_picture: Option<String>,
+    /// Whether joining through an invite has to be approved by an admin
+    pub approval_required: bool,
+    pub members: Vec<GetGroupChatMember>,
 }
 
+/// Fields which are missing are kept
 #[derive(Deserialize, ToSchema)]
 pub struct PatchGroupChat {
-    pub group_picture: String,
+    pub name: Option<String>,
+    /// An empty description removes the current one
+    pub description: Option<String>,
+    pub group_picture: Option<String>,
+    pub approval_required: Option<bool>,
 }
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetLiveness {
+    /// Always `ok`
+    pub status: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetReadiness {
+    /// Always `ready`, instances which aren't ready respond with a problem
+    pub status: String,
+    pub applied_migrations: usize,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetVersion {
+    pub version: String,
+    /// Commit the server has been built from, if it was known at build time
+    pub git_commit: Option<String>,
+    /// Names of the applied migrations in the order they have been applied
+    pub applied_migrations: Vec<String>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatKind {
+    GroupChat,
+    PrivateChat,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetInboxMessage {
+    pub message_id: i32,
+    /// Empty if the author has been erased
+    pub author_id: Option<i32>,
+    pub preview: Option<String>,
+    pub sent_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetInboxEntry {
+    pub kind: ChatKind,
+    pub chat_id: i32,
+    /// Name of the chat, only set for group chats
+    pub name: Option<String>,
+    /// Other participant, only set for private chats whose participant hasn't been erased
+    pub other_profile_id: Option<i32>,
+    pub last_message: Option<GetInboxMessage>,
+    pub last_activity: NaiveDateTime,
+    pub unread_count: u64,
+}
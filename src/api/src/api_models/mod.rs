@@ -1,5 +1,16 @@
+pub mod auth_schema;
+pub mod erasure_schema;
+pub mod export_schema;
+pub mod gateway_event_schema;
+pub mod group_chat_invite_schema;
+pub mod group_chat_join_request_schema;
 pub mod group_chat_member_schema;
 pub mod group_chat_message_schema;
 pub mod group_chat_schema;
+pub mod health_schema;
+pub mod inbox_schema;
+pub mod pagination_schema;
+pub mod private_chat_schema;
 pub mod private_message_schema;
 pub mod profile_schema;
+pub mod read_state_schema;
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Return messages older than this cursor
+    pub before: Option<String>,
+    /// Return messages newer than this cursor
+    pub after: Option<String>,
+    /// Maximum number of messages, between 1 and 100 (default 50)
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,25 @@
+use entities::private_chat;
+use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetPrivateChat {
+    pub private_chat_id: i32,
+    /// Empty if the participant has been erased
+    pub first_profile_id: Option<i32>,
+    /// Empty if the participant has been erased
+    pub second_profile_id: Option<i32>,
+    pub creation_date: NaiveDateTime,
+}
+
+impl From<private_chat::Model> for GetPrivateChat {
+    fn from(private_chat: private_chat::Model) -> Self {
+        GetPrivateChat {
+            private_chat_id: private_chat.private_chat_id,
+            first_profile_id: private_chat.first_profile_id,
+            second_profile_id: private_chat.second_profile_id,
+            creation_date: private_chat.creation_date,
+        }
+    }
+}
@@ -1,18 +1,62 @@
+use database::private_chat_last_read_message_id;
+use entities::{private_chat, private_message};
 use serde::{Deserialize, Serialize};
+use serde_with::chrono::NaiveDateTime;
 use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
 pub struct PostPrivateMessage {
-    pub sender_id: i32,
     pub recipient_id: i32,
     pub content: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetPrivateMessage {
-    pub sender_id: i32,
-    pub recipient_id: i32,
+    pub private_message_id: i32,
+    pub private_chat_id: i32,
+    /// Empty if the sender has been erased
+    pub sender_id: Option<i32>,
+    /// Empty if the recipient has been erased
+    pub recipient_id: Option<i32>,
     pub content: Option<String>,
+    pub sent_at: NaiveDateTime,
+    pub edited_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    /// Whether the recipient has read the message, either 0 or 1
+    pub seen_by: u64,
+}
+
+impl GetPrivateMessage {
+    /// Checks whether the read cursor of the recipient has reached the message.
+    pub fn with_read_state(mut self, private_chat: &private_chat::Model) -> Self {
+        let last_read_message_id = self
+            .recipient_id
+            .and_then(|recipient_id| private_chat_last_read_message_id(private_chat, recipient_id));
+        self.seen_by = (last_read_message_id >= Some(self.private_message_id)) as u64;
+        self
+    }
+}
+
+impl From<private_message::Model> for GetPrivateMessage {
+    fn from(message: private_message::Model) -> Self {
+        GetPrivateMessage {
+            private_message_id: message.private_message_id,
+            private_chat_id: message.private_chat_id,
+            sender_id: message.sender_id,
+            recipient_id: message.recipient_id,
+            content: message.content,
+            sent_at: message.sent_at,
+            edited_at: message.edited_at,
+            deleted_at: message.deleted_at,
+            seen_by: 0,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetPrivateMessagePage {
+    pub messages: Vec<GetPrivateMessage>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PostReadState {
+    /// Newest message which has been read, defaults to the newest message of the chat
+    pub message_id: Option<i32>,
+}
+
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub struct GetReadState {
+    pub chat_id: i32,
+    pub profile_id: i32,
+    pub last_read_message_id: Option<i32>,
+}
@@ -0,0 +1,73 @@
+use crate::error::ApiError;
+use crate::AppState;
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::*;
+use database::*;
+use entities::{profile, session};
+use futures::future::LocalBoxFuture;
+
+/// Profile of the caller, resolved from the bearer token of the request.
+///
+/// Handlers taking this extractor reject requests without a valid session with
+/// `401 Unauthorized`.
+pub struct AuthenticatedProfile {
+    pub profile: profile::Model,
+    pub session: session::Model,
+}
+
+impl FromRequest for AuthenticatedProfile {
+    type Error = ApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let data = req.app_data::<web::Data<AppState>>().cloned();
+        let access_token = bearer_token(req);
+
+        Box::pin(async move {
+            let (data, access_token) = match (data, access_token) {
+                (Some(data), Some(access_token)) => (data, access_token),
+                _ => return Err(ApiError::unauthorized("Missing bearer token.")),
+            };
+
+            authenticate_token(&access_token, &data).await
+        })
+    }
+}
+
+/// Resolves an access token to the session and profile it belongs to.
+pub(crate) async fn authenticate_token(
+    access_token: &str,
+    data: &AppState,
+) -> Result<AuthenticatedProfile, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let session = get_session_by_token(access_token, db_connection)
+        .await
+        .map_err(invalid_token)?;
+
+    let profile = get_profile_by_id(session.profile_id, db_connection)
+        .await
+        .map_err(invalid_token)?;
+
+    Ok(AuthenticatedProfile { profile, session })
+}
+
+/// Only unknown sessions and profiles reject the token, other errors are passed through.
+fn invalid_token(err: database::Error) -> ApiError {
+    match err {
+        database::Error::NotFound { .. } => ApiError::unauthorized("Invalid or expired token."),
+        err => err.into(),
+    }
+}
+
+pub(crate) fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let authorization = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = authorization.split_once(' ')?;
+
+    if !scheme.eq_ignore_ascii_case("Bearer") || token.trim().is_empty() {
+        return None;
+    }
+
+    Some(token.trim().to_owned())
+}
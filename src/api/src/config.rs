@@ -0,0 +1,522 @@
+use database::{mariadb_url_from_env, DatabaseConfig, ErasurePolicy, PasswordConfig};
+use log::LevelFilter;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Configuration file which is read if no other file has been given.
+pub const DEFAULT_CONFIG_FILE: &str = "sorume.toml";
+
+/// Settings of the server.
+///
+/// Values are layered: the TOML configuration file is overridden by environment variables,
+/// which are overridden by command line flags. Missing values fall back to the defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub tls: TlsConfig,
+    pub database: DatabaseConfig,
+    pub log: LogConfig,
+    pub telemetry: TelemetryConfig,
+    pub limits: LimitsConfig,
+    pub password: PasswordConfig,
+    pub session: SessionConfig,
+    pub erasure: ErasureConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: IpAddr,
+    pub port: u16,
+    /// Number of worker threads, defaults to the number of physical CPU cores
+    pub workers: Option<usize>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: IpAddr::from([0, 0, 0, 0]),
+            port: 8080,
+            workers: None,
+        }
+    }
+}
+
+/// Certificate and private key of the HTTPS listener, TLS is disabled without them.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    pub certificate_path: Option<PathBuf>,
+    pub private_key_path: Option<PathBuf>,
+    /// Port of a plain HTTP listener which redirects every request to HTTPS
+    pub redirect_port: Option<u16>,
+    /// Interval in which the certificate files are checked for changes, 0 only reloads on SIGHUP
+    pub reload_interval_seconds: u64,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        TlsConfig {
+            certificate_path: None,
+            private_key_path: None,
+            redirect_port: None,
+            reload_interval_seconds: 60,
+        }
+    }
+}
+
+impl TlsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.certificate_path.is_some() || self.private_key_path.is_some()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub file: PathBuf,
+    pub file_level: LevelFilter,
+    pub terminal_level: LevelFilter,
+    /// The log file is always written as JSON lines
+    pub terminal_format: LogFormat,
+    pub rotation: LogRotation,
+    /// Size in bytes after which the log file is rotated, used with `rotation = "size"`
+    pub max_file_size: usize,
+    /// Number of rotated log files which are kept
+    pub max_files: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            file: PathBuf::from("sorume-server.log"),
+            file_level: LevelFilter::Info,
+            terminal_level: LevelFilter::Warn,
+            terminal_format: LogFormat::Text,
+            rotation: LogRotation::Daily,
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected text or json".to_owned()),
+        }
+    }
+}
+
+/// When the log file is rotated
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+    Weekly,
+    Size,
+}
+
+impl FromStr for LogRotation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "never" => Ok(LogRotation::Never),
+            "hourly" => Ok(LogRotation::Hourly),
+            "daily" => Ok(LogRotation::Daily),
+            "weekly" => Ok(LogRotation::Weekly),
+            "size" => Ok(LogRotation::Size),
+            _ => Err("expected never, hourly, daily, weekly or size".to_owned()),
+        }
+    }
+}
+
+/// Export of tracing spans via OTLP, disabled without an endpoint.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP endpoint of the collector, e.g. `http://localhost:4318/v1/traces`
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+    /// Fraction of the traces which are exported, traces started by clients keep their decision
+    pub sample_ratio: f64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            otlp_endpoint: None,
+            service_name: "sorume-server".to_owned(),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Maximum size of JSON request bodies in bytes
+    pub max_json_body_size: usize,
+    /// Maximum number of concurrent connections per worker
+    pub max_connections: usize,
+    pub keep_alive_seconds: u64,
+    pub client_request_timeout_seconds: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_json_body_size: 64 * 1024,
+            max_connections: 25_000,
+            keep_alive_seconds: 5,
+            client_request_timeout_seconds: 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// Hours after which access tokens expire
+    pub lifetime_hours: u32,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig { lifetime_hours: 24 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErasureConfig {
+    /// What happens to the messages of erased profiles
+    pub policy: ErasurePolicy,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read { path: PathBuf, reason: String },
+    Parse { path: PathBuf, reason: String },
+    Variable { name: String, reason: String },
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, reason } => {
+                write!(f, "Couldn't read {}: {}", path.display(), reason)
+            }
+            ConfigError::Parse { path, reason } => {
+                write!(
+                    f,
+                    "Invalid configuration file {}: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            ConfigError::Variable { name, reason } => write!(f, "Invalid {}: {}", name, reason),
+            ConfigError::Invalid(reason) => write!(f, "Invalid configuration: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads the configuration file and applies the environment variables on top of it.
+    ///
+    /// Without an explicit `path`, `SORUME_CONFIG` or `sorume.toml` is used if it exists.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        dotenvy::dotenv().ok();
+
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var_os("SORUME_CONFIG").map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Config::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Config::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Config::default(),
+        };
+
+        config.apply_env()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let content = fs::read_to_string(path).map_err(|err| ConfigError::Read {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        })?;
+
+        toml::from_str(&content).map_err(|err| ConfigError::Parse {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        })
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        env_override(&mut self.server.bind_address, "SORUME_SERVER_BIND_ADDRESS")?;
+        env_override(&mut self.server.port, "SORUME_SERVER_PORT")?;
+        env_override_option(&mut self.server.workers, "SORUME_SERVER_WORKERS")?;
+
+        env_override_option(
+            &mut self.tls.certificate_path,
+            "SORUME_TLS_CERTIFICATE_PATH",
+        )?;
+        env_override_option(
+            &mut self.tls.private_key_path,
+            "SORUME_TLS_PRIVATE_KEY_PATH",
+        )?;
+        env_override_option(&mut self.tls.redirect_port, "SORUME_TLS_REDIRECT_PORT")?;
+        env_override(
+            &mut self.tls.reload_interval_seconds,
+            "SORUME_TLS_RELOAD_INTERVAL_SECONDS",
+        )?;
+
+        env_override(&mut self.database.url, "DATABASE_URL")?;
+        env_override(&mut self.database.url, "SORUME_DATABASE_URL")?;
+        env_override(
+            &mut self.database.max_connections,
+            "SORUME_DATABASE_MAX_CONNECTIONS",
+        )?;
+        env_override(
+            &mut self.database.min_connections,
+            "SORUME_DATABASE_MIN_CONNECTIONS",
+        )?;
+        env_override(
+            &mut self.database.connect_timeout_seconds,
+            "SORUME_DATABASE_CONNECT_TIMEOUT_SECONDS",
+        )?;
+
+        env_override(&mut self.log.file, "SORUME_LOG_FILE")?;
+        env_override(&mut self.log.file_level, "SORUME_LOG_FILE_LEVEL")?;
+        env_override(&mut self.log.terminal_level, "SORUME_LOG_TERMINAL_LEVEL")?;
+        env_override(&mut self.log.terminal_format, "SORUME_LOG_TERMINAL_FORMAT")?;
+        env_override(&mut self.log.rotation, "SORUME_LOG_ROTATION")?;
+        env_override(&mut self.log.max_file_size, "SORUME_LOG_MAX_FILE_SIZE")?;
+        env_override(&mut self.log.max_files, "SORUME_LOG_MAX_FILES")?;
+
+        env_override_option(
+            &mut self.telemetry.otlp_endpoint,
+            "SORUME_TELEMETRY_OTLP_ENDPOINT",
+        )?;
+        env_override(
+            &mut self.telemetry.service_name,
+            "SORUME_TELEMETRY_SERVICE_NAME",
+        )?;
+        env_override(
+            &mut self.telemetry.sample_ratio,
+            "SORUME_TELEMETRY_SAMPLE_RATIO",
+        )?;
+
+        env_override(
+            &mut self.limits.max_json_body_size,
+            "SORUME_LIMITS_MAX_JSON_BODY_SIZE",
+        )?;
+        env_override(
+            &mut self.limits.max_connections,
+            "SORUME_LIMITS_MAX_CONNECTIONS",
+        )?;
+        env_override(
+            &mut self.limits.keep_alive_seconds,
+            "SORUME_LIMITS_KEEP_ALIVE_SECONDS",
+        )?;
+        env_override(
+            &mut self.limits.client_request_timeout_seconds,
+            "SORUME_LIMITS_CLIENT_REQUEST_TIMEOUT_SECONDS",
+        )?;
+
+        // the unprefixed variables predate the configuration file and are still honored
+        env_override(&mut self.password.memory_cost, "PASSWORD_MEMORY_COST")?;
+        env_override(
+            &mut self.password.memory_cost,
+            "SORUME_PASSWORD_MEMORY_COST",
+        )?;
+        env_override(&mut self.password.time_cost, "PASSWORD_TIME_COST")?;
+        env_override(&mut self.password.time_cost, "SORUME_PASSWORD_TIME_COST")?;
+        env_override(&mut self.password.parallelism, "PASSWORD_PARALLELISM")?;
+        env_override(
+            &mut self.password.parallelism,
+            "SORUME_PASSWORD_PARALLELISM",
+        )?;
+
+        env_override(&mut self.session.lifetime_hours, "SESSION_LIFETIME_HOURS")?;
+        env_override(
+            &mut self.session.lifetime_hours,
+            "SORUME_SESSION_LIFETIME_HOURS",
+        )?;
+
+        env_override(&mut self.erasure.policy, "ERASURE_POLICY")?;
+        env_override(&mut self.erasure.policy, "SORUME_ERASURE_POLICY")?;
+
+        Ok(())
+    }
+
+    /// Fills in derived values and checks the configuration, so that the server doesn't fail
+    /// halfway through its startup.
+    pub fn validate(&mut self) -> Result<(), ConfigError> {
+        if self.database.url.is_empty() {
+            self.database.url = mariadb_url_from_env().ok_or_else(|| {
+                ConfigError::Invalid(
+                    "No database has been configured. Set database.url, DATABASE_URL or all \
+                     MARIADB_* variables."
+                        .to_owned(),
+                )
+            })?;
+        }
+
+        if self.server.port == 0 {
+            return Err(invalid("server.port must not be 0."));
+        }
+
+        if self.server.workers == Some(0) {
+            return Err(invalid("server.workers must be at least 1."));
+        }
+
+        if self.database.max_connections == 0 {
+            return Err(invalid("database.max_connections must be at least 1."));
+        }
+
+        if self.database.min_connections > self.database.max_connections {
+            return Err(invalid(
+                "database.min_connections must not exceed database.max_connections.",
+            ));
+        }
+
+        if self.limits.max_json_body_size == 0 || self.limits.max_connections == 0 {
+            return Err(invalid(
+                "limits.max_json_body_size and limits.max_connections must be at least 1.",
+            ));
+        }
+
+        if self.log.max_file_size == 0 || self.log.max_files == 0 {
+            return Err(invalid(
+                "log.max_file_size and log.max_files must be at least 1.",
+            ));
+        }
+
+        if let Some(endpoint) = &self.telemetry.otlp_endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                return Err(invalid(
+                    "telemetry.otlp_endpoint must be an http:// or https:// URL.",
+                ));
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.telemetry.sample_ratio) {
+            return Err(invalid("telemetry.sample_ratio must be between 0 and 1."));
+        }
+
+        if let Err(reason) = self.password.validate() {
+            return Err(invalid(format!(
+                "password.memory_cost, password.time_cost and password.parallelism are out of \
+                 range: {}",
+                reason
+            )));
+        }
+
+        if self.session.lifetime_hours == 0 {
+            return Err(invalid("session.lifetime_hours must be at least 1."));
+        }
+
+        if self.tls.is_enabled() {
+            for (name, path) in [
+                ("tls.certificate_path", &self.tls.certificate_path),
+                ("tls.private_key_path", &self.tls.private_key_path),
+            ] {
+                match path {
+                    Some(path) if path.is_file() => {}
+                    Some(path) => {
+                        return Err(invalid(format!(
+                            "{} {} is not a readable file.",
+                            name,
+                            path.display()
+                        )))
+                    }
+                    None => return Err(invalid(format!("{} is required for TLS.", name))),
+                }
+            }
+        }
+
+        match self.tls.redirect_port {
+            Some(_) if !self.tls.is_enabled() => {
+                return Err(invalid(
+                    "tls.redirect_port requires a certificate and a private key.",
+                ))
+            }
+            Some(port) if port == 0 || port == self.server.port => {
+                return Err(invalid(
+                    "tls.redirect_port must neither be 0 nor the port of the server.",
+                ))
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid(reason.into())
+}
+
+fn parse_variable<T>(name: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| ConfigError::Variable {
+        name: name.to_owned(),
+        reason: format!("{:?} ({})", value, err),
+    })
+}
+
+fn env_override<T>(target: &mut T, name: &str) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = env::var(name) {
+        *target = parse_variable(name, &value)?;
+    }
+
+    Ok(())
+}
+
+fn env_override_option<T>(target: &mut Option<T>, name: &str) -> Result<(), ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    if let Ok(value) = env::var(name) {
+        *target = Some(parse_variable(name, &value)?);
+    }
+
+    Ok(())
+}
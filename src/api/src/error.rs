@@ -0,0 +1,145 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpResponse, ResponseError};
+use database::GroupChatRole;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+pub(crate) const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Error response as described in RFC 7807
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI of the problem type, `urn:sorume:problem:<code>`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Stable error code, which can be used by clients to handle specific errors
+    pub code: String,
+}
+
+/// Error returned by the request handlers, rendered as problem details.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: String,
+    detail: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: impl Into<String>, detail: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code: code.into(),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn bad_request(code: impl Into<String>, detail: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, code, detail)
+    }
+
+    pub fn unauthorized(detail: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::UNAUTHORIZED, "unauthorized", detail)
+    }
+
+    pub fn forbidden(code: impl Into<String>, detail: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::FORBIDDEN, code, detail)
+    }
+
+    pub fn service_unavailable(code: impl Into<String>, detail: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::SERVICE_UNAVAILABLE, code, detail)
+    }
+
+    pub fn internal() -> Self {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            "An unexpected error occurred.",
+        )
+    }
+
+    pub fn not_group_chat_member() -> Self {
+        ApiError::forbidden(
+            "not_group_chat_member",
+            "You are not a member of this group chat.",
+        )
+    }
+
+    pub fn group_chat_role_required(role: GroupChatRole) -> Self {
+        let detail = match role {
+            GroupChatRole::Owner => "Only the owner of this group chat may do this.",
+            _ => "Only admins and the owner of this group chat may do this.",
+        };
+
+        ApiError::forbidden("group_chat_role_required", detail)
+    }
+
+    pub fn not_private_chat_participant() -> Self {
+        ApiError::forbidden(
+            "not_private_chat_participant",
+            "You are not a participant of this private chat.",
+        )
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.detail)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let problem = ProblemDetails {
+            problem_type: format!("urn:sorume:problem:{}", self.code),
+            title: self.status.canonical_reason().unwrap_or("Error").to_owned(),
+            status: self.status.as_u16(),
+            detail: self.detail.to_owned(),
+            code: self.code.to_owned(),
+        };
+
+        let mut response = HttpResponse::build(self.status);
+        response.insert_header((header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE));
+
+        if self.status == StatusCode::UNAUTHORIZED {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        response.json(problem)
+    }
+}
+
+impl From<database::Error> for ApiError {
+    fn from(err: database::Error) -> Self {
+        match err {
+            database::Error::NotFound { entity, .. } => ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("{}_not_found", entity.name()),
+                err.to_string(),
+            ),
+            database::Error::Validation(detail) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                detail,
+            ),
+            database::Error::Conflict(detail) => {
+                ApiError::new(StatusCode::CONFLICT, "conflict", detail)
+            }
+            database::Error::Forbidden(detail) => ApiError::forbidden("forbidden", detail),
+            database::Error::Db(err) => {
+                error!("Unexpected database error: {}", err);
+                ApiError::internal()
+            }
+        }
+    }
+}
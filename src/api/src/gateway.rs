@@ -0,0 +1,139 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use database::sea_orm::DbConn;
+use database::*;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use log::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Fans out gateway events to the WebSocket connections of the affected profiles.
+///
+/// A profile may be connected with several clients at once, each one owning its own channel.
+/// Channels of closed connections are dropped the next time an event is published to them.
+#[derive(Default)]
+pub struct EventBroker {
+    subscribers: Mutex<HashMap<i32, Vec<Subscriber>>>,
+}
+
+struct Subscriber {
+    session_id: i32,
+    sender: UnboundedSender<String>,
+}
+
+impl EventBroker {
+    pub fn subscribe(&self, profile_id: i32, session_id: i32) -> UnboundedReceiver<String> {
+        let (sender, receiver) = unbounded();
+
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry(profile_id)
+            .or_default()
+            .push(Subscriber { session_id, sender });
+
+        receiver
+    }
+
+    /// Drops the channels opened with a session, which ends their gateway connections.
+    pub fn disconnect_session(&self, profile_id: i32, session_id: i32) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        if let Some(profile_subscribers) = subscribers.get_mut(&profile_id) {
+            profile_subscribers.retain(|subscriber| subscriber.session_id != session_id);
+
+            if profile_subscribers.is_empty() {
+                subscribers.remove(&profile_id);
+            }
+        }
+    }
+
+    /// Drops every channel of a profile, which ends all of its gateway connections.
+    pub fn disconnect_profile(&self, profile_id: i32) {
+        self.subscribers.lock().unwrap().remove(&profile_id);
+    }
+
+    pub fn publish(&self, profile_ids: &[i32], event: &GatewayEvent) {
+        let payload = match serde_json::to_string(event) {
+            Ok(payload) => payload,
+            Err(err) => {
+                error!("Unable to serialize gateway event: {}", err);
+                return;
+            }
+        };
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+
+        for profile_id in profile_ids {
+            if let Some(profile_subscribers) = subscribers.get_mut(profile_id) {
+                profile_subscribers
+                    .retain(|subscriber| subscriber.sender.unbounded_send(payload.clone()).is_ok());
+
+                if profile_subscribers.is_empty() {
+                    subscribers.remove(profile_id);
+                }
+            }
+        }
+    }
+
+    /// Publishes an event to both participants of a private chat, skipping erased profiles.
+    pub fn publish_to_participants(&self, participant_ids: [Option<i32>; 2], event: &GatewayEvent) {
+        let profile_ids: Vec<i32> = participant_ids.into_iter().flatten().collect();
+        self.publish(&profile_ids, event);
+    }
+
+    /// Publishes an event to every current member of a group chat.
+    pub async fn publish_to_group(
+        &self,
+        group_chat_id: i32,
+        event: &GatewayEvent,
+        connection: &DbConn,
+    ) {
+        let profile_ids = group_member_ids(group_chat_id, connection).await;
+        self.publish(&profile_ids, event);
+    }
+
+    /// Publishes an event to the admins and the owner of a group chat.
+    pub async fn publish_to_group_admins(
+        &self,
+        group_chat_id: i32,
+        event: &GatewayEvent,
+        connection: &DbConn,
+    ) {
+        let profile_ids = group_admin_ids(group_chat_id, connection).await;
+        self.publish(&profile_ids, event);
+    }
+}
+
+/// Resolves the profiles subscribed to the events of a group chat.
+///
+/// Has to be called before deleting memberships, if the removed members should be notified.
+pub async fn group_member_ids(group_chat_id: i32, connection: &DbConn) -> Vec<i32> {
+    match get_members_of_group(group_chat_id, connection).await {
+        Ok(members) => members.iter().map(|member| member.profile_id).collect(),
+        Err(err) => {
+            warn!(
+                "R: Unable to resolve members of group chat {:?} for gateway event: {}",
+                group_chat_id, err
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Resolves the admins and the owner of a group chat, who are notified about join requests.
+pub async fn group_admin_ids(group_chat_id: i32, connection: &DbConn) -> Vec<i32> {
+    match get_members_of_group(group_chat_id, connection).await {
+        Ok(members) => members
+            .iter()
+            .filter(|member| GroupChatRole::of(member) >= GroupChatRole::Admin)
+            .map(|member| member.profile_id)
+            .collect(),
+        Err(err) => {
+            warn!(
+                "R: Unable to resolve admins of group chat {:?} for gateway event: {}",
+                group_chat_id, err
+            );
+            Vec::new()
+        }
+    }
+}
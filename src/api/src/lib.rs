@@ -1,42 +1,161 @@
 mod api_models;
-mod logger;
-mod openapi;
+mod authentication;
+pub mod config;
+mod error;
+mod gateway;
+pub mod logger;
+pub mod metrics;
+pub mod openapi;
+mod pagination;
+mod policy;
+pub mod request_id;
 mod services;
+pub mod telemetry;
+pub mod tls;
 
 use actix_web::*;
+use config::{Config, LimitsConfig};
 use database::sea_orm::DatabaseConnection;
 use database::*;
+use error::ApiError;
+use futures::future;
 use services::*;
+use std::sync::Arc;
+use std::time::Duration;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-struct AppState {
+pub struct AppState {
     db_connection: DatabaseConnection,
+    event_broker: gateway::EventBroker,
+    erasure_policy: ErasurePolicy,
+    password_config: PasswordConfig,
+    session_lifetime_hours: u32,
+    metrics: metrics::Metrics,
+}
+
+impl AppState {
+    pub fn new(mut db_connection: DatabaseConnection, config: &Config) -> Self {
+        let metrics = metrics::Metrics::default();
+
+        let query_metrics = metrics.clone();
+        db_connection.set_metric_callback(move |info| query_metrics.observe_query(info));
+
+        AppState {
+            db_connection,
+            event_broker: gateway::EventBroker::default(),
+            erasure_policy: config.erasure.policy,
+            password_config: config.password.clone(),
+            session_lifetime_hours: config.session.lifetime_hours,
+            metrics,
+        }
+    }
+}
+
+/// Registers every route of the API together with the extractor configuration. Used by the server
+/// and by the integration tests, which expect the app data to be provided separately.
+pub fn api_config(limits: LimitsConfig) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.app_data(
+            web::JsonConfig::default()
+                .limit(limits.max_json_body_size)
+                .error_handler(|err, _| {
+                    ApiError::bad_request("invalid_body", err.to_string()).into()
+                }),
+        )
+        .app_data(
+            web::PathConfig::default().error_handler(|err, _| {
+                ApiError::bad_request("invalid_path", err.to_string()).into()
+            }),
+        )
+        .app_data(
+            web::QueryConfig::default().error_handler(|err, _| {
+                ApiError::bad_request("invalid_query", err.to_string()).into()
+            }),
+        )
+        .configure(auth_service::auth_config)
+        .configure(gateway_service::gateway_config)
+        .configure(health_service::health_config)
+        .configure(profile_service::profile_config)
+        .configure(export_service::export_config)
+        .configure(group_chat_service::group_chat_config)
+        .configure(inbox_service::inbox_config)
+        .configure(metrics_service::metrics_config)
+        .configure(private_chat_service::private_chat_config)
+        .configure(private_message_service::private_message_config)
+        .configure(group_chat_members_service::group_chat_members_config)
+        .configure(group_chat_invite_service::group_chat_invite_config)
+        .configure(group_chat_join_request_service::group_chat_join_request_config)
+        .configure(group_chat_message_service::group_chat_message_config);
+    }
 }
 
 #[actix_web::main]
-pub async fn run() -> std::io::Result<()> {
-    let db_connection = connect_to_database(true).await.unwrap();
-    let data = web::Data::new(AppState { db_connection });
+pub async fn run(config: Config) -> std::io::Result<()> {
+    let _log_guard = logger::create_logger(&config.log, &config.telemetry)?;
 
-    let openapi = openapi::ApiDoc::openapi();
+    let db_connection = connect_to_database(&config.database)
+        .await
+        .map_err(std::io::Error::other)?;
+    let data = web::Data::new(AppState::new(db_connection, &config));
 
-    logger::create_logger();
+    let openapi = openapi::ApiDoc::openapi();
+    let limits = config.limits;
 
-    HttpServer::new(move || {
+    let mut server = HttpServer::new(move || {
         App::new()
             .app_data(data.clone())
-            .configure(profile_service::profile_config)
-            .configure(group_chat_service::group_chat_config)
-            .configure(private_message_service::private_message_config)
-            .configure(group_chat_members_service::group_chat_members_config)
-            .configure(group_chat_service::group_chat_config)
-            .configure(group_chat_message_service::group_chat_message_config)
+            .wrap(middleware::from_fn(metrics::record_request_metrics))
+            .wrap(middleware::from_fn(request_id::assign_request_id))
+            .configure(api_config(limits))
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
             )
     })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    .max_connections(limits.max_connections)
+    .keep_alive(Duration::from_secs(limits.keep_alive_seconds))
+    .client_request_timeout(Duration::from_secs(limits.client_request_timeout_seconds));
+
+    if let Some(workers) = config.server.workers {
+        server = server.workers(workers);
+    }
+
+    let address = (config.server.bind_address, config.server.port);
+
+    let (certificate_path, private_key_path) =
+        match (&config.tls.certificate_path, &config.tls.private_key_path) {
+            (Some(certificate_path), Some(private_key_path)) => {
+                (certificate_path, private_key_path)
+            }
+            _ => return server.bind(address)?.run().await,
+        };
+
+    let resolver = Arc::new(tls::CertificateResolver::load(
+        certificate_path,
+        private_key_path,
+    )?);
+    let poll_interval = match config.tls.reload_interval_seconds {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    };
+    tls::watch_certificates(resolver.clone(), poll_interval);
+
+    let server = server
+        .bind_rustls(address, tls::server_config(resolver))?
+        .run();
+
+    match config.tls.redirect_port {
+        Some(redirect_port) => {
+            let https_port = config.server.port;
+            let redirect_server =
+                HttpServer::new(move || App::new().configure(tls::redirect_config(https_port)))
+                    .workers(1)
+                    .bind((config.server.bind_address, redirect_port))?
+                    .run();
+
+            future::try_join(server, redirect_server).await?;
+            Ok(())
+        }
+        None => server.await,
+    }
 }
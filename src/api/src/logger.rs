@@ -1,22 +1,226 @@
-use log::info;
-use simplelog::*;
-use std::fs::File;
-
-pub fn create_logger() {
-    CombinedLogger::init(vec![
-        TermLogger::new(
-            LevelFilter::Warn,
-            Config::default(),
-            TerminalMode::Mixed,
-            ColorChoice::Auto,
-        ),
-        WriteLogger::new(
-            LevelFilter::Info,
-            Config::default(),
-            File::create("sorume-server.log").unwrap(),
-        ),
-    ])
-    .unwrap();
-
-    info!("Simplelog has been started successfully");
+use crate::config::{LogConfig, LogFormat, LogRotation, TelemetryConfig};
+use crate::telemetry;
+use chrono::{SecondsFormat, Utc};
+use file_rotate::compression::Compression;
+use file_rotate::suffix::{AppendTimestamp, FileLimit};
+use file_rotate::{ContentLimit, FileRotate, TimeFrequency};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use tracing::field::{Field, Visit};
+use tracing::{info, Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_log::{AsTrace, LogTracer, NormalizeEvent};
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+/// Maps the shorthands of `documentation/logging.md` to the `operation` field.
+const OPERATIONS: [(&str, &str); 4] = [
+    ("C: ", "create"),
+    ("R: ", "read"),
+    ("U: ", "update"),
+    ("D: ", "delete"),
+];
+
+/// Writes every event as a single JSON object.
+///
+/// The fields of all enclosing spans, e.g. the `request_id`, are added to the event, and the CRUD
+/// shorthand at the start of a message is moved into the `operation` field.
+pub struct JsonFormat;
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_owned(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_owned(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_owned(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_owned(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_owned(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_owned(), Value::from(format!("{:?}", value)));
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let normalized_metadata = event.normalized_metadata();
+        let metadata = normalized_metadata
+            .as_ref()
+            .unwrap_or_else(|| event.metadata());
+
+        let mut fields = Map::new();
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+
+                if let Some(span_fields) = extensions.get::<FormattedFields<N>>() {
+                    if let Ok(Value::Object(span_fields)) = serde_json::from_str(span_fields) {
+                        fields.extend(span_fields);
+                    }
+                }
+            }
+        }
+
+        event.record(&mut JsonVisitor(&mut fields));
+        fields.retain(|name, _| !name.starts_with("log.") && !name.starts_with("otel."));
+
+        let mut message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(message) => message.to_string(),
+            None => String::new(),
+        };
+
+        let mut output = Map::new();
+        output.insert(
+            "timestamp".to_owned(),
+            Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        );
+        output.insert("level".to_owned(), Value::from(metadata.level().as_str()));
+        output.insert("target".to_owned(), Value::from(metadata.target()));
+
+        if let Some((shorthand, operation)) = OPERATIONS
+            .iter()
+            .find(|(shorthand, _)| message.starts_with(shorthand))
+        {
+            message.drain(..shorthand.len());
+            output.insert("operation".to_owned(), Value::from(*operation));
+        }
+
+        output.insert("message".to_owned(), Value::from(message));
+        output.extend(fields);
+
+        writeln!(writer, "{}", Value::Object(output))
+    }
+}
+
+/// Formats events with [`JsonFormat`] and writes them to `make_writer`.
+pub fn json_layer<S, W>(make_writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .fmt_fields(JsonFields::new())
+        .event_format(JsonFormat)
+        .with_writer(make_writer)
+}
+
+fn rotating_file(log_config: &LogConfig) -> FileRotate<AppendTimestamp> {
+    let content_limit = match log_config.rotation {
+        LogRotation::Never => ContentLimit::None,
+        LogRotation::Hourly => ContentLimit::Time(TimeFrequency::Hourly),
+        LogRotation::Daily => ContentLimit::Time(TimeFrequency::Daily),
+        LogRotation::Weekly => ContentLimit::Time(TimeFrequency::Weekly),
+        LogRotation::Size => ContentLimit::BytesSurpassed(log_config.max_file_size),
+    };
+
+    FileRotate::new(
+        &log_config.file,
+        AppendTimestamp::default(FileLimit::MaxFiles(log_config.max_files)),
+        content_limit,
+        Compression::None,
+        #[cfg(unix)]
+        None,
+    )
+}
+
+/// Flushes the log file and the pending spans when it is dropped.
+pub struct LoggerGuard {
+    _file_guard: WorkerGuard,
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        if let Some(tracer_provider) = &self.tracer_provider {
+            if let Err(err) = tracer_provider.shutdown() {
+                eprintln!("Couldn't export the remaining spans: {}", err);
+            }
+        }
+    }
+}
+
+/// Installs the logger, which writes JSON lines to the rotated log file and to the terminal, and
+/// exports spans via OTLP if `telemetry_config` has an endpoint.
+///
+/// Records of the `log` crate, which is used by the `database` crate, are forwarded as well.
+pub fn create_logger(
+    log_config: &LogConfig,
+    telemetry_config: &TelemetryConfig,
+) -> io::Result<LoggerGuard> {
+    // the rotation panics on inaccessible files, which is reported as an error instead
+    if let Some(directory) = log_config.file.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_config.file)?;
+
+    let (file_writer, file_guard) = tracing_appender::non_blocking(rotating_file(log_config));
+
+    let tracer_provider = match &telemetry_config.otlp_endpoint {
+        Some(endpoint) => Some(telemetry::otlp_tracer_provider(telemetry_config, endpoint)?),
+        None => None,
+    };
+
+    let terminal_layer = match log_config.terminal_format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => json_layer(io::stdout).boxed(),
+    };
+
+    let subscriber = Registry::default()
+        .with(terminal_layer.with_filter(log_config.terminal_level.as_trace()))
+        .with(json_layer(file_writer).with_filter(log_config.file_level.as_trace()))
+        .with(tracer_provider.as_ref().map(telemetry::trace_layer));
+
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|err| io::Error::new(io::ErrorKind::AlreadyExists, err))?;
+
+    LogTracer::builder()
+        .with_max_level(log_config.file_level.max(log_config.terminal_level))
+        .init()
+        .map_err(|err| io::Error::new(io::ErrorKind::AlreadyExists, err))?;
+
+    info!("Logger has been started successfully");
+
+    if let Some(endpoint) = &telemetry_config.otlp_endpoint {
+        info!("Spans are exported to {}", endpoint);
+    }
+
+    Ok(LoggerGuard {
+        _file_guard: file_guard,
+        tracer_provider,
+    })
 }
@@ -0,0 +1,178 @@
+use crate::AppState;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use database::sea_orm::metric;
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::time::Instant;
+
+/// Label of requests which didn't match any route
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Prometheus metrics of the server, exposed on `/metrics`.
+///
+/// Every metric is registered in its own registry instead of the global one, so that several
+/// apps can run in the same process, e.g. in the integration tests.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    db_query_duration: HistogramVec,
+    gateway_connections: IntGauge,
+    pub(crate) active_sessions: IntGauge,
+    pub(crate) messages_sent: IntCounterVec,
+    pub(crate) profiles_created: IntCounter,
+    pub(crate) group_chats_created: IntCounter,
+}
+
+fn register<T: Collector + Clone + 'static>(registry: &Registry, collector: T) -> T {
+    registry
+        .register(Box::new(collector.clone()))
+        .expect("metric names are unique");
+
+    collector
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let registry = Registry::new_custom(Some("sorume".to_owned()), None).unwrap();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Handled HTTP requests"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time spent handling HTTP requests",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Time spent executing SQL statements",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+            ]),
+            &["statement", "outcome"],
+        )
+        .unwrap();
+        let gateway_connections =
+            IntGauge::new("gateway_connections", "Open gateway WebSocket connections").unwrap();
+        let active_sessions = IntGauge::new(
+            "active_sessions",
+            "Sessions which haven't expired, updated on every scrape",
+        )
+        .unwrap();
+        let messages_sent = IntCounterVec::new(
+            Opts::new("messages_sent_total", "Sent messages"),
+            &["chat_kind"],
+        )
+        .unwrap();
+        let profiles_created =
+            IntCounter::new("profiles_created_total", "Created profiles").unwrap();
+        let group_chats_created =
+            IntCounter::new("group_chats_created_total", "Created group chats").unwrap();
+
+        Metrics {
+            http_requests: register(&registry, http_requests),
+            http_request_duration: register(&registry, http_request_duration),
+            db_query_duration: register(&registry, db_query_duration),
+            gateway_connections: register(&registry, gateway_connections),
+            active_sessions: register(&registry, active_sessions),
+            messages_sent: register(&registry, messages_sent),
+            profiles_created: register(&registry, profiles_created),
+            group_chats_created: register(&registry, group_chats_created),
+            registry,
+        }
+    }
+}
+
+impl Metrics {
+    /// Records a statement executed by SeaORM, labelled with its kind, e.g. `select`.
+    pub fn observe_query(&self, info: &metric::Info<'_>) {
+        let statement = info
+            .statement
+            .sql
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let statement = match statement.as_str() {
+            "select" | "insert" | "update" | "delete" => statement.as_str(),
+            _ => "other",
+        };
+        let outcome = match info.failed {
+            true => "error",
+            false => "ok",
+        };
+
+        self.db_query_duration
+            .with_label_values(&[statement, outcome])
+            .observe(info.elapsed.as_secs_f64());
+    }
+
+    /// Counts an open gateway connection until the returned guard is dropped.
+    pub(crate) fn track_gateway_connection(&self) -> GatewayConnectionGuard {
+        self.gateway_connections.inc();
+        GatewayConnectionGuard(self.gateway_connections.clone())
+    }
+
+    /// Renders all metrics in the Prometheus text format.
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+pub(crate) struct GatewayConnectionGuard(IntGauge);
+
+impl Drop for GatewayConnectionGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Middleware counting requests and their duration per route. Routes are labelled with their
+/// pattern, e.g. `/profile/{profile_id}`, to keep the number of label values bounded.
+pub async fn record_request_metrics(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse, Error> {
+    let data = request.app_data::<web::Data<AppState>>().cloned();
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.call(request).await?;
+
+    if let Some(data) = data {
+        let route = response
+            .request()
+            .match_pattern()
+            .unwrap_or_else(|| UNMATCHED_ROUTE.to_owned());
+        let status = response.status().as_u16().to_string();
+
+        data.metrics
+            .http_requests
+            .with_label_values(&[&method, &route, &status])
+            .inc();
+        data.metrics
+            .http_request_duration
+            .with_label_values(&[&method, &route])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    Ok(response.map_into_boxed_body())
+}
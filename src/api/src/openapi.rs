@@ -1,16 +1,31 @@
 use crate::api_models::*;
+use crate::error;
 use crate::services::*;
 
-use utoipa::OpenApi;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        auth_service::login,
+        auth_service::logout,
+        gateway_service::gateway,
+        health_service::live,
+        health_service::ready,
+        health_service::version,
+        metrics_service::metrics,
         profile_service::new_profile,
         profile_service::get_profile,
         profile_service::get_profile_username,
         profile_service::update_profile,
         profile_service::delete_profile,
+        export_service::export_profile,
+        inbox_service::get_inbox,
+        private_chat_service::get_private_chat,
+        private_chat_service::get_private_chat_history,
+        private_chat_service::get_profile_private_chats,
+        private_chat_service::read_private_chat,
         private_message_service::new_private_message,
         private_message_service::get_private_message,
         private_message_service::get_private_chat_messages,
@@ -21,6 +36,7 @@ use utoipa::OpenApi;
         group_chat_service::get_group_chat,
         group_chat_service::update_group_chat,
         group_chat_service::delete_group_chat,
+        group_chat_service::read_group_chat,
         group_chat_message_service::new_group_chat_message,
         group_chat_message_service::get_all_group_chat_messages,
         group_chat_message_service::get_member_group_chat_messages,
@@ -31,24 +47,71 @@ use utoipa::OpenApi;
         group_chat_members_service::new_group_chat_member,
         group_chat_members_service::get_all_group_chat_members,
         group_chat_members_service::delete_all_group_chat_members,
-        group_chat_members_service::delete_single_group_chat_member
+        group_chat_members_service::delete_single_group_chat_member,
+        group_chat_members_service::update_group_chat_member_role,
+        group_chat_invite_service::new_group_chat_invite,
+        group_chat_invite_service::get_group_chat_invites,
+        group_chat_invite_service::revoke_group_chat_invite,
+        group_chat_invite_service::join_group_chat,
+        group_chat_join_request_service::get_group_chat_join_requests,
+        group_chat_join_request_service::accept_group_chat_join_request,
+        group_chat_join_request_service::delete_group_chat_join_request
     ),
     components(schemas(
+        error::ProblemDetails,
+        auth_schema::PostLogin,
+        auth_schema::GetSession,
+        gateway_event_schema::GatewayEvent,
+        erasure_schema::GetErasureAudit,
+        health_schema::GetLiveness,
+        health_schema::GetReadiness,
+        health_schema::GetVersion,
+        export_schema::ExportProfile,
+        export_schema::ExportGroupChatMembership,
+        export_schema::ExportRecord,
         profile_schema::PostProfile,
         profile_schema::GetProfile,
         profile_schema::PatchProfile,
+        inbox_schema::ChatKind,
+        inbox_schema::GetInboxMessage,
+        inbox_schema::GetInboxEntry,
+        private_chat_schema::GetPrivateChat,
         private_message_schema::PostPrivateMessage,
         private_message_schema::GetPrivateMessage,
+        private_message_schema::GetPrivateMessagePage,
         private_message_schema::PatchPrivateMessage,
         private_message_schema::DeletePostPrivateChat,
         group_chat_schema::PostGroupChat,
+        group_chat_schema::GetNewGroupChat,
         group_chat_schema::GetGroupChat,
         group_chat_schema::PatchGroupChat,
+        read_state_schema::PostReadState,
+        read_state_schema::GetReadState,
         group_chat_message_schema::PostGroupChatMessage,
         group_chat_message_schema::GetGroupChatMessage,
+        group_chat_message_schema::GetGroupChatMessagePage,
         group_chat_message_schema::PatchGroupChatMessage,
         group_chat_member_schema::PostGroupChatMember,
-        group_chat_member_schema::GetGroupChatMember
-    ))
+        group_chat_member_schema::GetGroupChatMember,
+        group_chat_member_schema::PatchGroupChatMember,
+        group_chat_member_schema::MemberRole,
+        group_chat_invite_schema::PostGroupChatInvite,
+        group_chat_invite_schema::GetGroupChatInvite,
+        group_chat_join_request_schema::GetGroupChatJoinRequest
+    )),
+    modifiers(&SecurityAddon)
 )]
 pub struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::api_models::pagination_schema::PageQuery;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use database::{MessageCursor, PagePosition};
+use serde_with::chrono::{DateTime, NaiveDateTime};
+
+/// Cursors are opaque to clients: they are base64 encoded, so their format can change later.
+fn encode_cursor(raw_cursor: String) -> String {
+    URL_SAFE_NO_PAD.encode(raw_cursor)
+}
+
+fn decode_cursor(cursor: &str) -> Option<String> {
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Message cursors keep the send time with full precision, so that they compare equal to the
+/// stored value of their own message.
+pub(crate) fn encode_message_cursor(send_time: NaiveDateTime, message_id: i32) -> String {
+    let send_time = send_time.and_utc();
+
+    encode_cursor(format!(
+        "{}.{:09}:{}",
+        send_time.timestamp(),
+        send_time.timestamp_subsec_nanos(),
+        message_id
+    ))
+}
+
+fn decode_message_cursor(cursor: &str) -> Option<MessageCursor> {
+    let raw_cursor = decode_cursor(cursor)?;
+    let (timestamp, message_id) = raw_cursor.split_once(':')?;
+    let (seconds, nanoseconds) = timestamp.split_once('.')?;
+
+    Some(MessageCursor {
+        send_time: DateTime::from_timestamp(seconds.parse().ok()?, nanoseconds.parse().ok()?)?
+            .naive_utc(),
+        message_id: message_id.parse().ok()?,
+    })
+}
+
+fn page_position<C>(
+    query: &PageQuery,
+    decode: impl Fn(&str) -> Option<C>,
+) -> Option<PagePosition<C>> {
+    match (&query.before, &query.after) {
+        (None, None) => Some(PagePosition::Latest),
+        (Some(before), None) => decode(before).map(PagePosition::Before),
+        (None, Some(after)) => decode(after).map(PagePosition::After),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+/// Resolves the requested page of a chat history, `None` if the cursors are invalid.
+pub(crate) fn message_page_position(query: &PageQuery) -> Option<PagePosition<MessageCursor>> {
+    page_position(query, decode_message_cursor)
+}
+
+/// Picks the cursor which continues a page in the direction it was requested.
+pub(crate) fn next_cursor<T, C>(
+    messages: &[T],
+    has_more: bool,
+    position: &PagePosition<C>,
+    encode: impl Fn(&T) -> String,
+) -> Option<String> {
+    if !has_more {
+        return None;
+    }
+
+    match position {
+        PagePosition::After(_) => messages.last().map(encode),
+        _ => messages.first().map(encode),
+    }
+}
@@ -0,0 +1,153 @@
+use crate::error::ApiError;
+use database::sea_orm::DbConn;
+use database::*;
+use entities::profile;
+
+/// Profiles may only be modified by their owner or by an administrator.
+pub(crate) fn can_manage_profile(caller: &profile::Model, profile_id: i32) -> bool {
+    caller.is_admin || caller.profile_id == profile_id
+}
+
+/// Private chats may only be accessed by one of their two participants.
+pub(crate) fn is_private_chat_participant(
+    caller: &profile::Model,
+    sender_id: Option<i32>,
+    recipient_id: Option<i32>,
+) -> bool {
+    sender_id == Some(caller.profile_id) || recipient_id == Some(caller.profile_id)
+}
+
+/// Messages may only be edited or deleted by their author or by an administrator. Messages of
+/// erased profiles are left to administrators.
+pub(crate) fn can_manage_message(caller: &profile::Model, author_id: Option<i32>) -> bool {
+    caller.is_admin || author_id == Some(caller.profile_id)
+}
+
+/// Actions within a group chat which depend on the role of the caller.
+#[derive(Clone, Copy)]
+pub(crate) enum GroupChatAction {
+    AddMembers,
+    /// Remove members of a lower role, everybody may leave on their own
+    RemoveMembers,
+    EditDetails,
+    DeleteOthersMessages,
+    /// Create, list and revoke invites
+    ManageInvites,
+    /// Accept or reject requests to join
+    ReviewJoinRequests,
+    ManageRoles,
+    /// Delete the group chat, or all of its members or messages at once
+    DeleteGroupChat,
+}
+
+impl GroupChatAction {
+    /// Permission matrix of the group chat roles, returns the lowest role allowed to act.
+    pub(crate) fn required_role(self) -> GroupChatRole {
+        match self {
+            GroupChatAction::AddMembers
+            | GroupChatAction::RemoveMembers
+            | GroupChatAction::EditDetails
+            | GroupChatAction::DeleteOthersMessages
+            | GroupChatAction::ManageInvites
+            | GroupChatAction::ReviewJoinRequests => GroupChatRole::Admin,
+            GroupChatAction::ManageRoles | GroupChatAction::DeleteGroupChat => GroupChatRole::Owner,
+        }
+    }
+}
+
+/// Group chat actions require a sufficient role of the caller, administrators may perform all
+/// of them.
+pub(crate) fn can_perform_group_chat_action(
+    caller: &profile::Model,
+    role: Option<GroupChatRole>,
+    action: GroupChatAction,
+) -> bool {
+    caller.is_admin || role.is_some_and(|role| role >= action.required_role())
+}
+
+/// Rejects callers who aren't members of the group chat or whose role is insufficient for the
+/// action, administrators are let through. Returns the role of the caller.
+pub(crate) async fn require_group_chat_action(
+    caller: &profile::Model,
+    group_chat_id: i32,
+    action: GroupChatAction,
+    connection: &DbConn,
+) -> Result<Option<GroupChatRole>, ApiError> {
+    let role = group_chat_role(caller, group_chat_id, connection).await;
+
+    if role.is_none() && !caller.is_admin {
+        return Err(ApiError::not_group_chat_member());
+    }
+
+    if !can_perform_group_chat_action(caller, role, action) {
+        return Err(ApiError::group_chat_role_required(action.required_role()));
+    }
+
+    Ok(role)
+}
+
+/// Role of the caller in a group chat, `None` if the caller isn't a member.
+///
+/// Lookup failures are treated as missing membership.
+pub(crate) async fn group_chat_role(
+    caller: &profile::Model,
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> Option<GroupChatRole> {
+    get_group_chat_membership(caller.profile_id, group_chat_id, connection)
+        .await
+        .ok()
+        .map(|membership| GroupChatRole::of(&membership))
+}
+
+/// Messages of others may only be deleted in group chats by members of a higher role than the
+/// author. Authors who have left or have been erased are treated as members.
+pub(crate) async fn can_delete_group_chat_messages_of(
+    caller: &profile::Model,
+    author_id: Option<i32>,
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> Result<bool, Error> {
+    if can_manage_message(caller, author_id) {
+        return Ok(true);
+    }
+
+    let role = match group_chat_role(caller, group_chat_id, connection).await {
+        Some(role)
+            if can_perform_group_chat_action(
+                caller,
+                Some(role),
+                GroupChatAction::DeleteOthersMessages,
+            ) =>
+        {
+            role
+        }
+        _ => return Ok(false),
+    };
+
+    let author_role = match author_id {
+        Some(author_id) => {
+            match get_group_chat_membership(author_id, group_chat_id, connection).await {
+                Ok(membership) => GroupChatRole::of(&membership),
+                Err(Error::NotFound { .. }) => GroupChatRole::Member,
+                Err(err) => return Err(err),
+            }
+        }
+        None => GroupChatRole::Member,
+    };
+
+    Ok(role > author_role)
+}
+
+/// Group chats may only be read and written by their members.
+///
+/// Lookup failures are treated as missing membership.
+pub(crate) async fn is_group_chat_participant(
+    caller: &profile::Model,
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> bool {
+    is_group_chat_member(caller.profile_id, group_chat_id, connection)
+        .await
+        .unwrap_or(false)
+}
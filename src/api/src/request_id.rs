@@ -0,0 +1,77 @@
+use crate::telemetry;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::Error;
+use tracing::field::Empty;
+use tracing::{info_span, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Maximum length of request IDs which are taken over from the client
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-_.:".contains(&byte))
+}
+
+/// Middleware which runs every request in a span carrying its `X-Request-Id`, so that the ID is
+/// part of every log line of the request, including those of the `database` operations.
+///
+/// The ID of the client is used if it is valid, otherwise a new one is generated. It is returned
+/// in the response in both cases. The span continues the trace of an incoming `traceparent`
+/// header and is named after the route pattern, e.g. `GET /profile/{profile_id}`.
+pub async fn assign_request_id(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse, Error> {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|request_id| request_id.to_str().ok())
+        .filter(|request_id| is_valid_request_id(request_id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // route patterns keep the number of span names bounded, unlike paths
+    let span_name = match request.match_pattern() {
+        Some(route) => format!("{} {}", request.method(), route),
+        None => request.method().to_string(),
+    };
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.path(),
+        otel.name = %span_name,
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.response.status_code = Empty,
+    );
+    // without an installed OpenTelemetry layer there is no trace to continue
+    let _ = span.set_parent(telemetry::extract_trace_context(request.headers()));
+
+    let mut response = next.call(request).instrument(span.clone()).await?;
+
+    span.record(
+        "http.response.status_code",
+        i64::from(response.status().as_u16()),
+    );
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+
+    if let Ok(request_id) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+    }
+
+    Ok(response.map_into_boxed_body())
+}
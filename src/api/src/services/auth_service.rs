@@ -0,0 +1,101 @@
+use crate::api_models::auth_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::AppState;
+use actix_web::*;
+use database::*;
+use log::*;
+
+/// Log in
+///
+/// Check the credentials of a profile and issue a new access token
+#[utoipa::path(
+    tag = "Authentication",
+    request_body = PostLogin,
+    responses(
+        (status = 200, body = GetSession),
+        (status = 401, description = "Invalid username or password", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[post("/auth/login")]
+pub(super) async fn login(
+    data: web::Data<AppState>,
+    credentials: web::Json<PostLogin>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let authentication_result = authenticate_profile(
+        &credentials.username,
+        &credentials.password,
+        &data.password_config,
+        db_connection,
+    )
+    .await;
+
+    let profile = match authentication_result {
+        Ok(profile) => profile,
+        Err(database::Error::Forbidden(_)) => {
+            return Err(ApiError::new(
+                http::StatusCode::UNAUTHORIZED,
+                "invalid_credentials",
+                "Invalid username or password.",
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let _ = delete_expired_sessions(db_connection).await;
+
+    let (access_token, session) = insert_session(
+        profile.profile_id,
+        data.session_lifetime_hours,
+        db_connection,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(GetSession {
+        access_token,
+        token_type: "Bearer".to_owned(),
+        expiration_date: session.expiration_date,
+    }))
+}
+
+/// Log out
+///
+/// Revoke the access token which was used to authenticate this request
+#[utoipa::path(
+    tag = "Authentication",
+    responses(
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/auth/logout")]
+pub(super) async fn logout(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    delete_session_by_id(caller.session.session_id, db_connection).await?;
+
+    data.event_broker
+        .disconnect_session(caller.profile.profile_id, caller.session.session_id);
+
+    info!(
+        "D: Session of profile has been revoked: {:?}",
+        caller.profile.profile_id
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
+}
+
+pub fn auth_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(login);
+    cfg.service(logout);
+}
@@ -0,0 +1,226 @@
+use crate::api_models::export_schema::*;
+use crate::api_models::group_chat_message_schema::GetGroupChatMessage;
+use crate::api_models::private_message_schema::GetPrivateMessage;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::AppState;
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use actix_web::*;
+use database::*;
+use entities::private_chat;
+use futures::{stream, StreamExt};
+use log::*;
+
+const EXPORT_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Part of the export which is produced next.
+enum ExportStep {
+    Memberships,
+    GroupMessages {
+        after_message_id: Option<i32>,
+    },
+    PrivateChats,
+    PrivateMessages {
+        private_chats: Vec<private_chat::Model>,
+        position: PagePosition<MessageCursor>,
+    },
+    Done,
+}
+
+/// Produces the records of a personal data export page by page, so that the export never has to
+/// be held in memory as a whole.
+struct ProfileExport {
+    data: web::Data<AppState>,
+    profile_id: i32,
+    step: ExportStep,
+}
+
+impl ProfileExport {
+    /// Returns the next chunk of JSON lines or `None` once the export is complete.
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, database::Error> {
+        let db_connection = &self.data.db_connection;
+        let mut records = Vec::new();
+
+        // empty chunks are skipped, they would terminate a chunked response early
+        while records.is_empty() {
+            match &mut self.step {
+                ExportStep::Memberships => {
+                    records.extend(
+                        get_memberships_of_profile(self.profile_id, db_connection)
+                            .await?
+                            .into_iter()
+                            .map(|group_chat| ExportRecord::GroupChatMembership(group_chat.into())),
+                    );
+                    self.step = ExportStep::GroupMessages {
+                        after_message_id: None,
+                    };
+                }
+                ExportStep::GroupMessages { after_message_id } => {
+                    let messages = get_group_messages_of_author_page(
+                        self.profile_id,
+                        *after_message_id,
+                        MAX_PAGE_SIZE,
+                        db_connection,
+                    )
+                    .await?;
+
+                    self.step = match messages.last() {
+                        Some(message) if messages.len() as u64 == MAX_PAGE_SIZE => {
+                            ExportStep::GroupMessages {
+                                after_message_id: Some(message.message_id),
+                            }
+                        }
+                        _ => ExportStep::PrivateChats,
+                    };
+
+                    records.extend(messages.into_iter().map(|message| {
+                        ExportRecord::GroupChatMessage(GetGroupChatMessage::from(message))
+                    }));
+                }
+                ExportStep::PrivateChats => {
+                    let private_chats =
+                        get_private_chats_of_profile(self.profile_id, db_connection).await?;
+
+                    records.extend(
+                        private_chats
+                            .iter()
+                            .cloned()
+                            .map(|private_chat| ExportRecord::PrivateChat(private_chat.into())),
+                    );
+                    self.step = ExportStep::PrivateMessages {
+                        private_chats,
+                        position: PagePosition::Latest,
+                    };
+                }
+                ExportStep::PrivateMessages {
+                    private_chats,
+                    position,
+                } => {
+                    let Some(private_chat) = private_chats.last().cloned() else {
+                        self.step = ExportStep::Done;
+                        continue;
+                    };
+
+                    let page = get_messages_of_private_chat_page(
+                        private_chat.private_chat_id,
+                        *position,
+                        MAX_PAGE_SIZE,
+                        db_connection,
+                    )
+                    .await?;
+
+                    // pages are walked backwards, starting with the most recent messages
+                    match page.messages.first() {
+                        Some(message) if page.has_more => {
+                            *position = PagePosition::Before(MessageCursor {
+                                send_time: message.sent_at,
+                                message_id: message.private_message_id,
+                            });
+                        }
+                        _ => {
+                            private_chats.pop();
+                            *position = PagePosition::Latest;
+                        }
+                    }
+
+                    records.extend(page.messages.into_iter().map(|message| {
+                        ExportRecord::PrivateMessage(
+                            GetPrivateMessage::from(message).with_read_state(&private_chat),
+                        )
+                    }));
+                }
+                ExportStep::Done => return Ok(None),
+            }
+        }
+
+        Ok(Some(to_json_lines(&records)))
+    }
+}
+
+fn to_json_lines(records: &[ExportRecord]) -> Bytes {
+    let mut lines = Vec::new();
+
+    for record in records {
+        // serializing plain structs cannot fail
+        serde_json::to_writer(&mut lines, record).unwrap();
+        lines.push(b'\n');
+    }
+
+    Bytes::from(lines)
+}
+
+/// Export personal data of profile
+///
+/// Download everything stored about a specific profile as JSON lines. The first line contains the
+/// profile, followed by its group chat memberships, authored group chat messages, private chats and
+/// their messages. Only the owner of the profile may export it.
+#[utoipa::path(
+    tag = "Profile",
+    params(
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 200, description = "One record per line", body = ExportRecord, content_type = "application/x-ndjson"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to export this profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified profile", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/profile/{profile_id}/export")]
+pub(super) async fn export_profile(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    profile_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    if caller.profile.profile_id != profile_id.to_owned() {
+        return Err(ApiError::forbidden(
+            "profile_access_denied",
+            "You are not allowed to export this profile.",
+        ));
+    }
+
+    let profile = get_profile_by_id(profile_id.to_owned(), &data.db_connection).await?;
+    let first_chunk = to_json_lines(&[ExportRecord::Profile(profile.into())]);
+
+    info!("R: Exporting personal data of profile {:?}", profile_id);
+
+    let export = ProfileExport {
+        data: data.clone(),
+        profile_id: profile_id.to_owned(),
+        step: ExportStep::Memberships,
+    };
+
+    let remaining_chunks = stream::unfold(export, |mut export| async move {
+        match export.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), export)),
+            Ok(None) => None,
+            Err(err) => {
+                error!(
+                    "R: Export of profile {:?} failed: {}",
+                    export.profile_id, err
+                );
+                export.step = ExportStep::Done;
+                Some((Err(ApiError::from(err)), export))
+            }
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(EXPORT_CONTENT_TYPE)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"sorume-export-{}.ndjson\"",
+                profile_id
+            ),
+        ))
+        .streaming(stream::once(async { Ok::<_, ApiError>(first_chunk) }).chain(remaining_chunks)))
+}
+
+pub fn export_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(export_profile);
+}
@@ -0,0 +1,144 @@
+use crate::api_models::gateway_event_schema::*;
+use crate::authentication::{authenticate_token, bearer_token};
+use crate::error::*;
+use crate::AppState;
+use actix_web::*;
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream, ProtocolError, Session};
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::{stream, StreamExt};
+use std::time::{Duration, Instant};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Connections which haven't answered a ping for this long are closed.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(75);
+
+enum GatewayInput {
+    Client(Result<Message, ProtocolError>),
+    Event(String),
+    Disconnected,
+    Heartbeat,
+}
+
+/// Open gateway connection
+///
+/// Upgrade to a WebSocket connection which receives a `GatewayEvent` for every change in
+/// the private and group chats of the authenticated profile
+#[utoipa::path(
+    tag = "Gateway",
+    params(GatewayQuery),
+    responses(
+        (status = 101, description = "Switching protocols", body = GatewayEvent),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/ws")]
+pub(super) async fn gateway(
+    req: HttpRequest,
+    body: web::Payload,
+    data: web::Data<AppState>,
+    query: web::Query<GatewayQuery>,
+) -> Result<HttpResponse, Error> {
+    let access_token = match bearer_token(&req).or(query.into_inner().access_token) {
+        Some(access_token) => access_token,
+        None => return Err(ApiError::unauthorized("Missing bearer token.").into()),
+    };
+
+    let caller = authenticate_token(&access_token, &data).await?;
+
+    let (response, session, message_stream) = actix_ws::handle(&req, body)?;
+    let events = data
+        .event_broker
+        .subscribe(caller.profile.profile_id, caller.session.session_id);
+    let connection_guard = data.metrics.track_gateway_connection();
+
+    rt::spawn(async move {
+        run_gateway_session(session, message_stream, events, access_token, data).await;
+        drop(connection_guard);
+    });
+
+    Ok(response)
+}
+
+/// Forwards events to the client until either side closes the connection.
+///
+/// The session is checked again with every heartbeat, so that revoked and expired tokens don't
+/// keep receiving events. Logging out and erasing the profile end the connection right away.
+async fn run_gateway_session(
+    mut session: Session,
+    message_stream: MessageStream,
+    events: UnboundedReceiver<String>,
+    access_token: String,
+    data: web::Data<AppState>,
+) {
+    let heartbeat = stream::unfold((), |_| async {
+        rt::time::sleep(HEARTBEAT_INTERVAL).await;
+        Some((GatewayInput::Heartbeat, ()))
+    })
+    .boxed_local();
+
+    // the broker drops the channel once the session is revoked
+    let events = events
+        .map(GatewayInput::Event)
+        .chain(stream::iter([GatewayInput::Disconnected]));
+
+    let mut inputs = stream::select(
+        stream::select(message_stream.map(GatewayInput::Client), events),
+        heartbeat,
+    );
+
+    let mut last_pong = Instant::now();
+
+    while let Some(input) = inputs.next().await {
+        let result = match input {
+            GatewayInput::Client(Ok(Message::Ping(bytes))) => session.pong(&bytes).await,
+            GatewayInput::Client(Ok(Message::Pong(_))) => {
+                last_pong = Instant::now();
+                Ok(())
+            }
+            GatewayInput::Client(Ok(Message::Close(reason))) => {
+                let _ = session.close(reason).await;
+                return;
+            }
+            GatewayInput::Client(Ok(_)) => Ok(()),
+            GatewayInput::Client(Err(_)) => break,
+            GatewayInput::Event(payload) => session.text(payload).await,
+            GatewayInput::Disconnected => {
+                let _ = session.close(Some(session_revoked())).await;
+                return;
+            }
+            GatewayInput::Heartbeat => {
+                if last_pong.elapsed() > CLIENT_TIMEOUT {
+                    break;
+                }
+
+                if authenticate_token(&access_token, &data).await.is_err() {
+                    let _ = session.close(Some(session_revoked())).await;
+                    return;
+                }
+
+                session.ping(b"").await
+            }
+        };
+
+        if result.is_err() {
+            return;
+        }
+    }
+
+    let _ = session.close(None).await;
+}
+
+fn session_revoked() -> CloseReason {
+    CloseReason {
+        code: CloseCode::Policy,
+        description: Some("Invalid or expired token.".to_owned()),
+    }
+}
+
+pub fn gateway_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(gateway);
+}
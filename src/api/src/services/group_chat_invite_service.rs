@@ -0,0 +1,219 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use crate::api_models::group_chat_invite_schema::*;
+use crate::api_models::group_chat_join_request_schema::GetGroupChatJoinRequest;
+use crate::api_models::group_chat_member_schema::GetGroupChatMember;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::policy::*;
+use crate::AppState;
+use actix_web::*;
+use database::*;
+
+/// Create invite for group chat
+///
+/// Create a shareable invite code for a specific group chat using its identifier and post data.
+/// Requires the admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Invite",
+    request_body = PostGroupChatInvite,
+    params(
+        ("group_chat_id", description = "Identifier of group chat")
+    ),
+    responses(
+        (status = 201, body = GetGroupChatInvite),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Expiry in the past or fewer than one use", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/group_chat/{group_chat_id}/invites/new")]
+pub(super) async fn new_group_chat_invite(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    group_chat_id: web::Path<i32>,
+    new_invite: web::Json<PostGroupChatInvite>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::ManageInvites,
+        db_connection,
+    )
+    .await?;
+
+    let new_invite = new_invite.into_inner();
+
+    let invite = insert_group_chat_invite(
+        group_chat_id.to_owned(),
+        caller.profile.profile_id,
+        InviteLimits {
+            expires_at: new_invite.expires_at,
+            max_uses: new_invite.max_uses,
+        },
+        db_connection,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(GetGroupChatInvite::from(invite)))
+}
+
+/// Get all invites of group chat
+///
+/// Retrieve all invites of a specific group chat using its identifier, including revoked and
+/// expired ones. Requires the admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Invite",
+    params(
+        ("group_chat_id", description = "Identifier of group chat")
+    ),
+    responses(
+        (status = 200, body = [GetGroupChatInvite]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/group_chat/{group_chat_id}/invites")]
+pub(super) async fn get_group_chat_invites(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    group_chat_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::ManageInvites,
+        db_connection,
+    )
+    .await?;
+
+    let invites: Vec<GetGroupChatInvite> =
+        get_invites_of_group(group_chat_id.to_owned(), db_connection)
+            .await?
+            .into_iter()
+            .map(GetGroupChatInvite::from)
+            .collect();
+
+    Ok(HttpResponse::Ok().json(invites))
+}
+
+/// Revoke invite of group chat
+///
+/// Revoke a specific invite of a given group chat using their identifiers, the code can't be used
+/// anymore afterwards. Requires the admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Invite",
+    params(
+        ("group_chat_id", description = "Identifier of group chat"),
+        ("invite_id", description = "Identifier of invite")
+    ),
+    responses(
+        (status = 200, body = GetGroupChatInvite),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified invite", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[delete("/group_chat/{group_chat_id}/invites/{invite_id}")]
+pub(super) async fn revoke_group_chat_invite(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, invite_id) = path.into_inner();
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id,
+        GroupChatAction::ManageInvites,
+        db_connection,
+    )
+    .await?;
+
+    let invite =
+        database::revoke_group_chat_invite(group_chat_id, invite_id, db_connection).await?;
+
+    Ok(HttpResponse::Ok().json(GetGroupChatInvite::from(invite)))
+}
+
+/// Join group chat with invite
+///
+/// Join the group chat of an invite code. If the group chat requires approval, a join request is
+/// created instead, which its admins accept or reject.
+#[utoipa::path(
+    tag = "Group Chat Invite",
+    params(
+        ("code", description = "Code of invite")
+    ),
+    responses(
+        (status = 201, description = "Joined the group chat", body = GetGroupChatMember),
+        (status = 202, description = "Requested to join the group chat", body = GetGroupChatJoinRequest),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified invite", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The invite has been revoked, has expired or has been used up, or you are already a member or have already requested to join", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/invite/{code}/join")]
+pub(super) async fn join_group_chat(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    code: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let outcome =
+        join_group_chat_with_invite(&code, caller.profile.profile_id, db_connection).await?;
+
+    match outcome {
+        JoinOutcome::Joined(member) => {
+            data.event_broker
+                .publish_to_group(
+                    member.group_chat_id,
+                    &GatewayEvent::GroupChatMemberAdded {
+                        group_chat_id: member.group_chat_id,
+                        profile_id: member.profile_id,
+                    },
+                    db_connection,
+                )
+                .await;
+
+            Ok(HttpResponse::Created().json(GetGroupChatMember::from(member)))
+        }
+        JoinOutcome::Requested(join_request) => {
+            data.event_broker
+                .publish_to_group_admins(
+                    join_request.group_chat_id,
+                    &GatewayEvent::GroupChatJoinRequested(join_request.clone().into()),
+                    db_connection,
+                )
+                .await;
+
+            Ok(HttpResponse::Accepted().json(GetGroupChatJoinRequest::from(join_request)))
+        }
+    }
+}
+
+pub fn group_chat_invite_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(new_group_chat_invite);
+    cfg.service(get_group_chat_invites);
+    cfg.service(revoke_group_chat_invite);
+    cfg.service(join_group_chat);
+}
@@ -0,0 +1,170 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use crate::api_models::group_chat_join_request_schema::*;
+use crate::api_models::group_chat_member_schema::GetGroupChatMember;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::gateway::group_admin_ids;
+use crate::policy::*;
+use crate::AppState;
+use actix_web::*;
+use database::*;
+
+/// Get all join requests of group chat
+///
+/// Retrieve the pending requests to join a specific group chat using its identifier. Requires the
+/// admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Join Request",
+    params(
+        ("group_chat_id", description = "Identifier of group chat")
+    ),
+    responses(
+        (status = 200, body = [GetGroupChatJoinRequest]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/group_chat/{group_chat_id}/join_requests")]
+pub(super) async fn get_group_chat_join_requests(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    group_chat_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::ReviewJoinRequests,
+        db_connection,
+    )
+    .await?;
+
+    let join_requests: Vec<GetGroupChatJoinRequest> =
+        get_join_requests_of_group(group_chat_id.to_owned(), db_connection)
+            .await?
+            .into_iter()
+            .map(GetGroupChatJoinRequest::from)
+            .collect();
+
+    Ok(HttpResponse::Ok().json(join_requests))
+}
+
+/// Accept join request
+///
+/// Accept the pending request of a specific profile to join a given group chat using their
+/// identifiers, the profile becomes a member. Requires the admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Join Request",
+    params(
+        ("group_chat_id", description = "Identifier of group chat"),
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 201, body = GetGroupChatMember),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified join request", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/group_chat/{group_chat_id}/join_requests/{profile_id}/accept")]
+pub(super) async fn accept_group_chat_join_request(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id,
+        GroupChatAction::ReviewJoinRequests,
+        db_connection,
+    )
+    .await?;
+
+    let member =
+        database::accept_group_chat_join_request(group_chat_id, profile_id, db_connection).await?;
+
+    data.event_broker
+        .publish_to_group(
+            group_chat_id,
+            &GatewayEvent::GroupChatMemberAdded {
+                group_chat_id,
+                profile_id,
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Created().json(GetGroupChatMember::from(member)))
+}
+
+/// Reject or withdraw join request
+///
+/// Delete the pending request of a specific profile to join a given group chat using their
+/// identifiers. Profiles may withdraw their own requests, others require the admin or owner role.
+#[utoipa::path(
+    tag = "Group Chat Join Request",
+    params(
+        ("group_chat_id", description = "Identifier of group chat"),
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified join request", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[delete("/group_chat/{group_chat_id}/join_requests/{profile_id}")]
+pub(super) async fn delete_group_chat_join_request(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
+    let db_connection = &data.db_connection;
+
+    if !can_manage_profile(&caller.profile, profile_id) {
+        require_group_chat_action(
+            &caller.profile,
+            group_chat_id,
+            GroupChatAction::ReviewJoinRequests,
+            db_connection,
+        )
+        .await?;
+    }
+
+    database::delete_group_chat_join_request(group_chat_id, profile_id, db_connection).await?;
+
+    let mut profile_ids = group_admin_ids(group_chat_id, db_connection).await;
+    profile_ids.push(profile_id);
+
+    data.event_broker.publish(
+        &profile_ids,
+        &GatewayEvent::GroupChatJoinRequestDeleted {
+            group_chat_id,
+            profile_id,
+        },
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
+}
+
+pub fn group_chat_join_request_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_group_chat_join_requests);
+    cfg.service(accept_group_chat_join_request);
+    cfg.service(delete_group_chat_join_request);
+}
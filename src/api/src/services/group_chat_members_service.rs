@@ -1,11 +1,17 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
 use crate::api_models::group_chat_member_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::gateway::group_member_ids;
+use crate::policy::*;
 use crate::AppState;
 use actix_web::*;
 use database::*;
 
 /// Add profile to group chat
 ///
-/// Add a specific profile by post to an existing group chat
+/// Add a specific profile by post to an existing group chat. Requires the admin or owner role, new
+/// members start with the member role.
 #[utoipa::path(
     tag = "Group Chat Member",
     request_body = PostGroupChatMember,
@@ -14,100 +20,156 @@ use database::*;
     ),
     responses(
         (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat or profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The profile is already a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[post("/group_chat/{group_chat_id}/members/new")]
 pub(super) async fn new_group_chat_member(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
     new_group_chat_member: web::Json<PostGroupChatMember>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let insert_result = insert_group_chat_member(
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::AddMembers,
+        db_connection,
+    )
+    .await?;
+
+    let member = insert_group_chat_member(
         new_group_chat_member.profile_id,
         group_chat_id.to_owned(),
-        &db_connection,
+        GroupChatRole::Member,
+        db_connection,
     )
-    .await;
+    .await?;
 
-    match insert_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
-    }
+    data.event_broker
+        .publish_to_group(
+            member.group_chat_id,
+            &GatewayEvent::GroupChatMemberAdded {
+                group_chat_id: member.group_chat_id,
+                profile_id: member.profile_id,
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Created().body("Success!"))
 }
 
 /// Get all profiles of group chat
 ///
-/// Retrieve all profiles of a specific group chat using its identifier
+/// Retrieve all profiles of a specific group chat and their roles using its identifier
 #[utoipa::path(
     tag = "Group Chat Member",
     params(
         ("group_chat_id", description = "Identifier of group chat")
     ),
     responses(
-        (status = 201, body = [GetGroupChatMember]),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, body = [GetGroupChatMember]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/group_chat/{group_chat_id}/members")]
 pub(super) async fn get_all_group_chat_members(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_members_of_group(group_chat_id.to_owned(), db_connection).await;
-
-    if query_result.is_err() {
-        return HttpResponse::NotFound().body("Couldn't find the specified group chat!");
+    if !is_group_chat_participant(&caller.profile, group_chat_id.to_owned(), db_connection).await {
+        return Err(ApiError::not_group_chat_member());
     }
 
-    let mut group_members: Vec<GetGroupChatMember> = vec![];
-
-    for member in query_result.unwrap() {
-        let member_schema = GetGroupChatMember {
-            profile_id: member.profile_id,
-        };
-
-        group_members.push(member_schema);
-    }
+    let group_members: Vec<GetGroupChatMember> =
+        get_members_of_group(group_chat_id.to_owned(), db_connection)
+            .await?
+            .into_iter()
+            .map(GetGroupChatMember::from)
+            .collect();
 
-    HttpResponse::Ok().json(group_members)
+    Ok(HttpResponse::Ok().json(group_members))
 }
 
 /// Remove all profiles from group chat
 ///
-/// Remove all profiles of a specific group chat using its identifier
+/// Remove all profiles of a specific group chat using its identifier, except for the owner who
+/// keeps the group chat. Requires the owner role.
 #[utoipa::path(
     tag = "Group Chat Member",
     params(
         ("group_chat_id", description = "Identifier of group chat")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/group_chat/{group_chat_id}/members")]
 pub(super) async fn delete_all_group_chat_members(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_members_of_group(group_chat_id.to_owned(), db_connection).await;
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::DeleteGroupChat,
+        db_connection,
+    )
+    .await?;
+
+    let members = get_members_of_group(group_chat_id.to_owned(), db_connection).await?;
+    delete_members_of_group_except_owner(group_chat_id.to_owned(), db_connection).await?;
 
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
+    let member_ids: Vec<i32> = members.iter().map(|member| member.profile_id).collect();
+
+    for member in &members {
+        if GroupChatRole::of(member) == GroupChatRole::Owner {
+            continue;
+        }
+
+        data.event_broker.publish(
+            &member_ids,
+            &GatewayEvent::GroupChatMemberRemoved {
+                group_chat_id: group_chat_id.to_owned(),
+                profile_id: member.profile_id,
+            },
+        );
     }
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Remove single profile from group chat
 ///
-/// Remove a specific profile from a given group chat using their identifiers
+/// Remove a specific profile from a given group chat using their identifiers. Members may always
+/// leave, others can only be removed by admins and the owner if their role is lower. If the owner
+/// leaves, the ownership passes to the longest-standing admin, or member if there is no admin.
 #[utoipa::path(
     tag = "Group Chat Member",
     params(
@@ -115,31 +177,147 @@ pub(super) async fn delete_all_group_chat_members(
         ("profile_id", description = "Identifier of profile")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat or profile!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to remove this member", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat member", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/group_chat/{group_chat_id}/members/{profile_id}")]
 pub(super) async fn delete_single_group_chat_member(
     data: web::Data<AppState>,
-    group_chat_id: web::Path<i32>,
-    profile_id: web::Path<i32>,
-) -> impl Responder {
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_single_membership(
-        group_chat_id.to_owned(),
-        profile_id.to_owned(),
+    if !can_manage_profile(&caller.profile, profile_id) {
+        // others can only be removed by members of a higher role
+        let role = group_chat_role(&caller.profile, group_chat_id, db_connection).await;
+
+        let outranks_target = match role {
+            Some(role)
+                if can_perform_group_chat_action(
+                    &caller.profile,
+                    Some(role),
+                    GroupChatAction::RemoveMembers,
+                ) =>
+            {
+                let target_membership =
+                    get_group_chat_membership(profile_id, group_chat_id, db_connection).await?;
+
+                role > GroupChatRole::of(&target_membership)
+            }
+            _ => false,
+        };
+
+        if !outranks_target {
+            return Err(ApiError::forbidden(
+                "member_removal_denied",
+                "You are not allowed to remove this member.",
+            ));
+        }
+    }
+
+    let member_ids = group_member_ids(group_chat_id, db_connection).await;
+    let new_owner = delete_single_membership(group_chat_id, profile_id, db_connection).await?;
+
+    data.event_broker.publish(
+        &member_ids,
+        &GatewayEvent::GroupChatMemberRemoved {
+            group_chat_id,
+            profile_id,
+        },
+    );
+
+    if let Some(new_owner) = new_owner {
+        data.event_broker.publish(
+            &member_ids,
+            &GatewayEvent::GroupChatMemberRoleChanged {
+                group_chat_id,
+                profile_id: new_owner.profile_id,
+                role: MemberRole::Owner,
+            },
+        );
+    }
+
+    Ok(HttpResponse::Ok().body("Success!"))
+}
+
+/// Change role of group chat member
+///
+/// Promote or demote a specific member of a given group chat using their identifiers. Requires the
+/// owner role. Making a member the owner transfers the ownership, the previous owner becomes an
+/// admin.
+#[utoipa::path(
+    tag = "Group Chat Member",
+    request_body = PatchGroupChatMember,
+    params(
+        ("group_chat_id", description = "Identifier of group chat"),
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 200, body = GetGroupChatMember),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat member", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The owner has to transfer the ownership instead", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[patch("/group_chat/{group_chat_id}/members/{profile_id}")]
+pub(super) async fn update_group_chat_member_role(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+    updated_fields: web::Json<PatchGroupChatMember>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
+    let db_connection = &data.db_connection;
+
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id,
+        GroupChatAction::ManageRoles,
         db_connection,
     )
-    .await;
+    .await?;
 
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => {
-            HttpResponse::NotFound().body("Couldn't find the specified group chat or profile!")
+    let changed_memberships = database::update_group_chat_member_role(
+        group_chat_id,
+        profile_id,
+        updated_fields.role.into(),
+        db_connection,
+    )
+    .await?;
+
+    let member_ids = group_member_ids(group_chat_id, db_connection).await;
+    let mut target_membership = None;
+
+    for membership in changed_memberships {
+        let membership = GetGroupChatMember::from(membership);
+
+        data.event_broker.publish(
+            &member_ids,
+            &GatewayEvent::GroupChatMemberRoleChanged {
+                group_chat_id,
+                profile_id: membership.profile_id,
+                role: membership.role,
+            },
+        );
+
+        if membership.profile_id == profile_id {
+            target_membership = Some(membership);
         }
     }
+
+    Ok(HttpResponse::Ok().json(target_membership))
 }
 
 pub fn group_chat_members_config(cfg: &mut web::ServiceConfig) {
@@ -147,4 +325,5 @@ pub fn group_chat_members_config(cfg: &mut web::ServiceConfig) {
     cfg.service(get_all_group_chat_members);
     cfg.service(delete_all_group_chat_members);
     cfg.service(delete_single_group_chat_member);
+    cfg.service(update_group_chat_member_role);
 }
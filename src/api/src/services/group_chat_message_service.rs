@@ -1,4 +1,10 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
 use crate::api_models::group_chat_message_schema::*;
+use crate::api_models::pagination_schema::PageQuery;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::pagination::*;
+use crate::policy::*;
 use crate::AppState;
 use actix_web::*;
 use database::*;
@@ -14,29 +20,58 @@ use database::*;
     ),
     responses(
         (status = 201, description = "Success!"),
-        (status = 500, description = "Error!")
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Messages need a content", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[post("/group_chat/{group_chat_id}/messages/new")]
 pub(super) async fn new_group_chat_message(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
     new_group_chat_message: web::Json<PostGroupChatMessage>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let insert_result = insert_group_chat_message(
-        new_group_chat_message.author_id,
+    if !is_group_chat_participant(&caller.profile, group_chat_id.to_owned(), db_connection).await {
+        return Err(ApiError::not_group_chat_member());
+    }
+
+    let content = match &new_group_chat_message.content {
+        Some(content) => content.to_owned(),
+        None => {
+            return Err(database::Error::Validation("Messages need a content.".to_owned()).into())
+        }
+    };
+
+    let message = insert_group_chat_message(
+        caller.profile.profile_id,
         group_chat_id.to_owned(),
-        new_group_chat_message.content.to_owned().unwrap(),
-        &db_connection,
+        content,
+        db_connection,
     )
-    .await;
-
-    match insert_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::InternalServerError().body("Error!"),
-    }
+    .await?;
+
+    data.metrics
+        .messages_sent
+        .with_label_values(&["group_chat"])
+        .inc();
+
+    data.event_broker
+        .publish_to_group(
+            message.chat_id,
+            &GatewayEvent::GroupChatMessageCreated(message.into()),
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Created().body("Success!"))
 }
 // TODO: combine group id and message id as primary key?
 /*#[get("/group_chat/{group_chat_i}/messages/{message_id}")]
@@ -70,47 +105,67 @@ async fn get_group_chat_messages(
     HttpResponse::Ok().json(messages)
 }*/
 
-/// Get all messages of group chat
+/// Get messages of group chat
 ///
-/// Retrieve all chat messages of a specific group chat using its identifier
+/// Retrieve a page of chat messages of a specific group chat using its identifier. Messages are
+/// ordered chronologically, `next_cursor` continues in the requested direction.
 #[utoipa::path(
     tag = "Group Chat Message",
     params(
-        ("group_chat_id", description = "Identifier of group chat")
+        ("group_chat_id", description = "Identifier of group chat"),
+        PageQuery
     ),
     responses(
-        (status = 201, body = [GetGroupChatMessage]),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, body = GetGroupChatMessagePage),
+        (status = 400, description = "Invalid cursor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/group_chat/{group_chat_id}/messages")]
 pub(super) async fn get_all_group_chat_messages(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+    page_query: web::Query<PageQuery>,
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_messages_of_group(group_chat_id.to_owned(), db_connection).await;
-
-    if query_result.is_err() {
-        return HttpResponse::NotFound().body("Couldn't find the specified group chat!");
-    };
-
-    let mut messages: Vec<GetGroupChatMessage> = vec![];
-
-    for message in query_result.unwrap() {
-        let message_schema = GetGroupChatMessage {
-            message_id: message.message_id,
-            author_id: message.author_id,
-            send_time: message.send_time,
-            content: message.content,
-            chat_id: message.chat_id,
-        };
-
-        messages.push(message_schema);
+    if !is_group_chat_participant(&caller.profile, group_chat_id.to_owned(), db_connection).await {
+        return Err(ApiError::not_group_chat_member());
     }
 
-    HttpResponse::Ok().json(messages)
+    let position = message_page_position(&page_query)
+        .ok_or_else(|| ApiError::bad_request("invalid_cursor", "The cursor is invalid."))?;
+
+    let page = get_messages_of_group_page(
+        group_chat_id.to_owned(),
+        position,
+        page_size(page_query.limit),
+        db_connection,
+    )
+    .await?;
+
+    let next_cursor = next_cursor(&page.messages, page.has_more, &position, |message| {
+        encode_message_cursor(message.send_time, message.message_id)
+    });
+
+    let members = get_members_of_group(group_chat_id.to_owned(), db_connection)
+        .await
+        .unwrap_or_default();
+
+    Ok(HttpResponse::Ok().json(GetGroupChatMessagePage {
+        messages: page
+            .messages
+            .into_iter()
+            .map(|message| GetGroupChatMessage::from(message).with_read_state(&members))
+            .collect(),
+        next_cursor,
+    }))
 }
 
 /// Get all messages of a profile in a group chat
@@ -123,44 +178,41 @@ pub(super) async fn get_all_group_chat_messages(
         ("profile_id", description = "Identifier of profile")
     ),
     responses(
-        (status = 201, body = [GetGroupChatMessage]),
-        (status = 404, description = "Couldn't find the specified group chat or profile!"),
+        (status = 200, body = [GetGroupChatMessage]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat or profile", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/group_chat/{group_chat_id}/members/{profile_id}/messages")]
 pub(super) async fn get_member_group_chat_messages(
     data: web::Data<AppState>,
-    group_chat_id: web::Path<i32>,
-    profile_id: web::Path<i32>,
-) -> impl Responder {
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
     let db_connection = &data.db_connection;
 
-    let query_result = get_group_messages_of_profile(
-        group_chat_id.to_owned(),
-        profile_id.to_owned(),
-        db_connection,
-    )
-    .await;
-
-    if query_result.is_err() {
-        return HttpResponse::NotFound().body("Couldn't find the specified group chat or profile!");
-    };
+    if !is_group_chat_participant(&caller.profile, group_chat_id, db_connection).await {
+        return Err(ApiError::not_group_chat_member());
+    }
 
-    let mut messages: Vec<GetGroupChatMessage> = vec![];
+    let group_messages =
+        get_group_messages_of_profile(profile_id, group_chat_id, db_connection).await?;
 
-    for message in query_result.unwrap() {
-        let message_schema = GetGroupChatMessage {
-            message_id: message.message_id,
-            author_id: message.author_id,
-            send_time: message.send_time,
-            content: message.content,
-            chat_id: message.chat_id,
-        };
+    let members = get_members_of_group(group_chat_id, db_connection)
+        .await
+        .unwrap_or_default();
 
-        messages.push(message_schema);
-    }
+    let messages: Vec<GetGroupChatMessage> = group_messages
+        .into_iter()
+        .map(|message| GetGroupChatMessage::from(message).with_read_state(&members))
+        .collect();
 
-    HttpResponse::Ok().json(messages)
+    Ok(HttpResponse::Ok().json(messages))
 }
 
 /// Update group chat message
@@ -171,103 +223,180 @@ pub(super) async fn get_member_group_chat_messages(
     request_body = PatchGroupChatMessage,
     params(
         ("group_chat_id", description = "Identifier of group chat"),
-        ("group_chat_message_id", description = "Identifier of group chat message")
+        ("message_id", description = "Identifier of group chat message")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat or message!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat message", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 // TODO: affected by primary key change
 #[patch("/group_chat/{group_chat_id}/messages/{message_id}")]
 pub(super) async fn update_group_chat_message(
     data: web::Data<AppState>,
-    group_chat_id: web::Path<i32>,
-    message_id: web::Path<i32>,
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
     updated_fields: web::Json<PatchGroupChatMessage>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, message_id) = path.into_inner();
     let db_connection = &data.db_connection;
 
-    let update_result = update_group_message(
-        message_id.to_owned(),
-        updated_fields.content.to_owned(),
-        db_connection,
-    )
-    .await;
+    let message = get_group_message_by_id(message_id, db_connection).await?;
 
-    match update_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => {
-            HttpResponse::NotFound().body("Couldn't find the specified group chat or message!")
-        }
+    if message.chat_id != group_chat_id {
+        return Err(
+            database::Error::not_found(EntityKind::GroupChatMessage, message.message_id).into(),
+        );
+    }
+
+    if !can_manage_message(&caller.profile, message.author_id) {
+        return Err(ApiError::forbidden(
+            "message_access_denied",
+            "You are not allowed to modify this message.",
+        ));
     }
+
+    let message =
+        update_group_message(message_id, updated_fields.content.to_owned(), db_connection).await?;
+
+    data.event_broker
+        .publish_to_group(
+            message.chat_id,
+            &GatewayEvent::GroupChatMessageUpdated(message.into()),
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete message in a group chat
 ///
-/// Delete a specific message in a specific group chat using their identifiers
+/// Delete a specific message in a specific group chat using their identifiers. Admins and the
+/// owner of the group chat may delete the messages of members with a lower role.
 #[utoipa::path(
     tag = "Group Chat Message",
     params(
         ("group_chat_id", description = "Identifier of group chat"),
-        ("group_chat_message_id", description = "Identifier of group chat message")
+        ("message_id", description = "Identifier of group chat message")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified private message!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat message", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 // TODO: affected by primary key change
 #[delete("/group_chat/{group_chat_id}/message/{message_id}")]
 pub(super) async fn delete_group_chat_message(
     data: web::Data<AppState>,
-    group_chat_id: web::Path<i32>,
-    message_id: web::Path<i32>,
-) -> impl Responder {
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, message_id) = path.into_inner();
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_single_group_message(message_id.to_owned(), db_connection).await;
+    let message = get_group_message_by_id(message_id, db_connection).await?;
 
-    // TODO: error text
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => {
-            HttpResponse::NotFound().body("Couldn't find the specified group chat or message!")
-        }
+    if message.chat_id != group_chat_id {
+        return Err(
+            database::Error::not_found(EntityKind::GroupChatMessage, message.message_id).into(),
+        );
     }
+
+    if !can_delete_group_chat_messages_of(
+        &caller.profile,
+        message.author_id,
+        group_chat_id,
+        db_connection,
+    )
+    .await?
+    {
+        return Err(ApiError::forbidden(
+            "message_access_denied",
+            "You are not allowed to modify this message.",
+        ));
+    }
+
+    delete_single_group_message(message_id, db_connection).await?;
+
+    data.event_broker
+        .publish_to_group(
+            group_chat_id,
+            &GatewayEvent::GroupChatMessageDeleted {
+                group_chat_id,
+                message_id,
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete all messages of group chat
 ///
-/// Delete all messages of a specific group chat using its identifier
+/// Delete all messages of a specific group chat using its identifier. Requires the owner role.
 #[utoipa::path(
     tag = "Group Chat Message",
     params(
         ("group_chat_id", description = "Identifier of group chat")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role is insufficient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/group_chat/{group_chat_id}/messages")]
 pub(super) async fn delete_all_group_chat_messages(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_messages_of_group(group_chat_id.to_owned(), db_connection).await;
-
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
-    }
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::DeleteGroupChat,
+        db_connection,
+    )
+    .await?;
+
+    delete_messages_of_group(group_chat_id.to_owned(), db_connection).await?;
+
+    data.event_broker
+        .publish_to_group(
+            group_chat_id.to_owned(),
+            &GatewayEvent::GroupChatMessagesDeleted {
+                group_chat_id: group_chat_id.to_owned(),
+                author_id: None,
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete all messages of profile in group chat
 ///
-/// Delete all messages of a specific profile in a specific group chat using their identifiers
+/// Delete all messages of a specific profile in a specific group chat using their identifiers.
+/// Admins and the owner of the group chat may delete the messages of members with a lower role.
 #[utoipa::path(
     tag = "Group Chat Message",
     params(
@@ -275,31 +404,52 @@ pub(super) async fn delete_all_group_chat_messages(
         ("profile_id", description = "Identifier of profile")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat or profile!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify these messages", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat or profile", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/group_chat/{group_chat_id}/members/{profile_id}/messages")]
 pub(super) async fn delete_profile_group_chat_messages(
     data: web::Data<AppState>,
-    group_chat_id: web::Path<i32>,
-    profile_id: web::Path<i32>,
-) -> impl Responder {
+    caller: AuthenticatedProfile,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let (group_chat_id, profile_id) = path.into_inner();
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_group_messages_of_profile(
-        profile_id.to_owned(),
-        group_chat_id.to_owned(),
+    if !can_delete_group_chat_messages_of(
+        &caller.profile,
+        Some(profile_id),
+        group_chat_id,
         db_connection,
     )
-    .await;
-
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => {
-            HttpResponse::NotFound().body("Couldn't find the specified group chat or profile!")
-        }
+    .await?
+    {
+        return Err(ApiError::forbidden(
+            "message_access_denied",
+            "You are not allowed to modify these messages.",
+        ));
     }
+
+    delete_group_messages_of_profile(profile_id, group_chat_id, db_connection).await?;
+
+    data.event_broker
+        .publish_to_group(
+            group_chat_id,
+            &GatewayEvent::GroupChatMessagesDeleted {
+                group_chat_id,
+                author_id: Some(profile_id),
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 pub fn group_chat_message_config(cfg: &mut web::ServiceConfig) {
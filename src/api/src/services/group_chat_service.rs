@@ -1,73 +1,134 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use crate::api_models::group_chat_member_schema::GetGroupChatMember;
 use crate::api_models::group_chat_schema::*;
+use crate::api_models::read_state_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::gateway::group_member_ids;
+use crate::policy::*;
 use crate::AppState;
 use actix_web::*;
 use database::*;
 
 /// Create new group chat
 ///
-/// Create a new group chat using post data
+/// Create a new group chat using post data. Member IDs of unknown profiles are skipped and listed
+/// in the response, the creator is always added as a member.
 #[utoipa::path(
     tag = "Group Chat",
     request_body = PostGroupChat,
     responses(
-        (status = 201, description = "Success!"),
-        (status = 500, description = "Error!")
+        (status = 201, body = GetNewGroupChat),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid name or description, or no member could be added", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
-// TODO: group picture is not sent to database
 #[post("/group_chat/new")]
 pub(super) async fn new_group_chat(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     new_group_chat: web::Json<PostGroupChat>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let result = insert_group_chat(new_group_chat.member_ids.to_owned(), &db_connection).await;
+    // the creator is always a member of the new group chat
+    let mut member_ids = vec![caller.profile.profile_id];
+    member_ids.extend(
+        new_group_chat
+            .member_ids
+            .iter()
+            .filter(|member_id| **member_id != caller.profile.profile_id),
+    );
 
-    match result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::InternalServerError().body("Error!"),
-    }
+    let NewGroupChat {
+        group_chat,
+        rejected_member_ids,
+    } = insert_group_chat(
+        caller.profile.profile_id,
+        &new_group_chat.name,
+        new_group_chat.description.as_deref(),
+        member_ids,
+        new_group_chat.group_picture.to_owned(),
+        db_connection,
+    )
+    .await?;
+
+    data.metrics.group_chats_created.inc();
+
+    data.event_broker
+        .publish_to_group(
+            group_chat.group_chat_id,
+            &GatewayEvent::GroupChatCreated {
+                group_chat_id: group_chat.group_chat_id,
+            },
+            db_connection,
+        )
+        .await;
+
+    Ok(HttpResponse::Created().json(GetNewGroupChat {
+        group_chat_id: group_chat.group_chat_id,
+        rejected_member_ids,
+    }))
 }
 
 /// Get group chat
 ///
-/// Get a specific group chat by its identifier
+/// Get a specific group chat and its members by its identifier
 #[utoipa::path(
     tag = "Group Chat",
     params(
         ("group_chat_id", description = "Identifier of group_chat")
     ),
     responses(
-        (status = 201, body = GetGroupChat),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, body = GetGroupChat),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/group_chat/{group_chat_id}")]
 pub(super) async fn get_group_chat(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_group_chat_by_id(group_chat_id.to_owned(), &db_connection).await;
+    if !is_group_chat_participant(&caller.profile, group_chat_id.to_owned(), db_connection).await {
+        return Err(ApiError::not_group_chat_member());
+    }
 
-    match query_result {
-        Ok(group_chat) => {
-            let group_chat_schema = GetGroupChat {
-                creation_date: group_chat.creation_date,
-                group_picture: group_chat.group_picture,
-            };
+    let group_chat = get_group_chat_by_id(group_chat_id.to_owned(), db_connection).await?;
+    let members = get_members_of_group(group_chat.group_chat_id, db_connection)
+        .await?
+        .into_iter()
+        .map(GetGroupChatMember::from)
+        .collect();
 
-            HttpResponse::Ok().json(group_chat_schema)
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
-    }
+    Ok(HttpResponse::Ok().json(GetGroupChat {
+        group_chat_id: group_chat.group_chat_id,
+        name: group_chat.name,
+        description: group_chat.description,
+        created_by: group_chat.created_by,
+        creation_date: group_chat.creation_date,
+        updated_at: group_chat.updated_at,
+        group_picture: group_chat.group_picture,
+        approval_required: group_chat.approval_required,
+        members,
+    }))
 }
 
 /// Update group chat
 ///
-/// Update a specific group chat using its identifier and patch data
+/// Update the name, description, picture or approval mode of a specific group chat using its
+/// identifier and patch data. Requires the admin or owner role.
 #[utoipa::path(
     tag = "Group Chat",
     request_body = PatchGroupChat,
@@ -75,65 +136,152 @@ pub(super) async fn get_group_chat(
         ("group_chat_id", description = "Identifier of group chat")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat!"),
-        (status = 500, description = "Failed!")
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role doesn't allow this", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid name or description", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[patch("/group_chat/{group_chat_id}")]
 pub(super) async fn update_group_chat(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     updated_fields: web::Json<PatchGroupChat>,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_group_chat_by_id(group_chat_id.to_owned(), &db_connection).await;
-
-    match query_result {
-        Ok(group_chat) => {
-            let update_result = database::update_group_chat(
-                group_chat.group_chat_id,
-                updated_fields.group_picture.to_owned(),
-                &db_connection,
-            )
-            .await;
-
-            match update_result {
-                Ok(_) => HttpResponse::Ok().body("Success!"),
-                Err(_) => HttpResponse::InternalServerError().body("Failed!"),
-            }
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
-    }
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::EditDetails,
+        db_connection,
+    )
+    .await?;
+
+    let updated_fields = updated_fields.into_inner();
+
+    database::update_group_chat(
+        group_chat_id.to_owned(),
+        GroupChatChanges {
+            name: updated_fields.name,
+            description: updated_fields.description,
+            group_picture: updated_fields.group_picture,
+            approval_required: updated_fields.approval_required,
+        },
+        db_connection,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete group chat
 ///
-/// Delete a specific group chat by its identifier
+/// Delete a specific group chat by its identifier. Requires the owner role.
 #[utoipa::path(
     tag = "Group Chat",
     params(
         ("group_chat_id", description = "Identifier of group chat")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified group chat!"),
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat or your role doesn't allow this", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/group_chat/delete/{group_chat_id}")]
 pub(super) async fn delete_group_chat(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     group_chat_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_group_chat_by_id(group_chat_id.to_owned(), &db_connection).await;
+    require_group_chat_action(
+        &caller.profile,
+        group_chat_id.to_owned(),
+        GroupChatAction::DeleteGroupChat,
+        db_connection,
+    )
+    .await?;
+
+    let member_ids = group_member_ids(group_chat_id.to_owned(), db_connection).await;
+    delete_group_chat_by_id(group_chat_id.to_owned(), db_connection).await?;
 
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified group chat!"),
+    data.event_broker.publish(
+        &member_ids,
+        &GatewayEvent::GroupChatDeleted {
+            group_chat_id: group_chat_id.to_owned(),
+        },
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
+}
+
+/// Mark group chat as read
+///
+/// Advance the read cursor of the caller in a specific group chat up to the given message, or the
+/// newest message if none is given. The cursor never moves backwards.
+#[utoipa::path(
+    tag = "Group Chat",
+    request_body = PostReadState,
+    params(
+        ("group_chat_id", description = "Identifier of group chat")
+    ),
+    responses(
+        (status = 200, body = GetReadState),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a member of this group chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified group chat or message", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/group_chat/{group_chat_id}/read")]
+pub(super) async fn read_group_chat(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    group_chat_id: web::Path<i32>,
+    read_state: web::Json<PostReadState>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    if !is_group_chat_participant(&caller.profile, group_chat_id.to_owned(), db_connection).await {
+        return Err(ApiError::not_group_chat_member());
     }
+
+    let membership = mark_group_chat_read(
+        caller.profile.profile_id,
+        group_chat_id.to_owned(),
+        read_state.message_id,
+        db_connection,
+    )
+    .await?;
+
+    let read_state = GetReadState {
+        chat_id: membership.group_chat_id,
+        profile_id: membership.profile_id,
+        last_read_message_id: membership.last_read_message_id,
+    };
+
+    let member_ids = group_member_ids(membership.group_chat_id, db_connection).await;
+    data.event_broker.publish(
+        &member_ids,
+        &GatewayEvent::GroupChatRead(read_state.clone()),
+    );
+
+    Ok(HttpResponse::Ok().json(read_state))
 }
 
 pub fn group_chat_config(cfg: &mut web::ServiceConfig) {
@@ -141,4 +289,5 @@ pub fn group_chat_config(cfg: &mut web::ServiceConfig) {
     cfg.service(get_group_chat);
     cfg.service(update_group_chat);
     cfg.service(delete_group_chat);
+    cfg.service(read_group_chat);
 }
@@ -0,0 +1,130 @@
+use crate::api_models::health_schema::*;
+use crate::error::*;
+use crate::AppState;
+use actix_web::*;
+use log::*;
+use migration::{Migrator, MigratorTrait};
+
+/// Check liveness
+///
+/// Respond as long as the process is able to serve requests, without touching the database
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, body = GetLiveness)
+    )
+)]
+#[get("/health/live")]
+pub(super) async fn live() -> impl Responder {
+    HttpResponse::Ok().json(GetLiveness {
+        status: "ok".to_owned(),
+    })
+}
+
+/// Check readiness
+///
+/// Check whether the database is reachable and all migrations have been applied, instances which
+/// aren't ready shouldn't receive traffic
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, body = GetReadiness),
+        (status = 503, description = "The database is unreachable or migrations are pending", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/health/ready")]
+pub(super) async fn ready(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    if let Err(err) = db_connection.ping().await {
+        warn!("R: Database is unreachable: {}", err);
+
+        return Err(ApiError::service_unavailable(
+            "database_unavailable",
+            "The database is unreachable.",
+        ));
+    }
+
+    let pending_migrations = match Migrator::get_pending_migrations(db_connection).await {
+        Ok(pending_migrations) => pending_migrations,
+        Err(err) => {
+            warn!("R: Unable to read the migration status: {}", err);
+
+            return Err(ApiError::service_unavailable(
+                "database_unavailable",
+                "The migration status couldn't be read.",
+            ));
+        }
+    };
+
+    if !pending_migrations.is_empty() {
+        let names: Vec<&str> = pending_migrations
+            .iter()
+            .map(|migration| migration.name())
+            .collect();
+
+        return Err(ApiError::service_unavailable(
+            "migrations_pending",
+            format!("Migrations are pending: {}.", names.join(", ")),
+        ));
+    }
+
+    let applied_migrations = match Migrator::get_applied_migrations(db_connection).await {
+        Ok(applied_migrations) => applied_migrations,
+        Err(err) => {
+            warn!("R: Unable to read the applied migrations: {}", err);
+
+            return Err(ApiError::service_unavailable(
+                "database_unavailable",
+                "The applied migrations couldn't be read.",
+            ));
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(GetReadiness {
+        status: "ready".to_owned(),
+        applied_migrations: applied_migrations.len(),
+    }))
+}
+
+/// Get version
+///
+/// Retrieve the version and git commit of the server together with the applied migrations
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, body = GetVersion),
+        (status = 503, description = "The database is unreachable", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/version")]
+pub(super) async fn version(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let applied_migrations = match Migrator::get_applied_migrations(db_connection).await {
+        Ok(applied_migrations) => applied_migrations,
+        Err(err) => {
+            warn!("R: Unable to read the applied migrations: {}", err);
+
+            return Err(ApiError::service_unavailable(
+                "database_unavailable",
+                "The applied migrations couldn't be read.",
+            ));
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(GetVersion {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        git_commit: option_env!("SORUME_GIT_COMMIT").map(str::to_owned),
+        applied_migrations: applied_migrations
+            .iter()
+            .map(|migration| migration.name().to_owned())
+            .collect(),
+    }))
+}
+
+pub fn health_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(live);
+    cfg.service(ready);
+    cfg.service(version);
+}
@@ -0,0 +1,111 @@
+use crate::api_models::inbox_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::policy::*;
+use crate::AppState;
+use actix_web::*;
+use database::*;
+use std::cmp::Reverse;
+
+/// Maximum number of characters of the last message shown in the inbox
+const PREVIEW_LENGTH: usize = 100;
+
+fn preview(content: Option<String>) -> Option<String> {
+    content.map(|content| content.chars().take(PREVIEW_LENGTH).collect())
+}
+
+/// Get inbox of profile
+///
+/// Retrieve all group chats and private chats of a specific profile with their last message and
+/// unread count, ordered by recent activity
+#[utoipa::path(
+    tag = "Inbox",
+    params(
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 200, body = [GetInboxEntry]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to access this profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/profile/{profile_id}/inbox")]
+pub(super) async fn get_inbox(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    profile_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    if !can_manage_profile(&caller.profile, profile_id.to_owned()) {
+        return Err(ApiError::forbidden(
+            "profile_access_denied",
+            "You are not allowed to access this profile.",
+        ));
+    }
+
+    let inbox = get_inbox_of_profile(profile_id.to_owned(), db_connection).await?;
+
+    let group_chat_entries = inbox.group_chats.into_iter().map(|summary| {
+        let last_message = summary.last_message.map(|message| GetInboxMessage {
+            message_id: message.message_id,
+            author_id: message.author_id,
+            preview: preview(message.content),
+            sent_at: message.send_time,
+        });
+
+        GetInboxEntry {
+            kind: ChatKind::GroupChat,
+            chat_id: summary.group_chat.group_chat_id,
+            name: Some(summary.group_chat.name),
+            other_profile_id: None,
+            last_activity: last_message
+                .as_ref()
+                .map_or(summary.group_chat.creation_date, |message| message.sent_at),
+            last_message,
+            unread_count: summary.unread_count,
+        }
+    });
+
+    let private_chat_entries = inbox.private_chats.into_iter().map(|summary| {
+        let last_message = summary.last_message.map(|message| GetInboxMessage {
+            message_id: message.private_message_id,
+            author_id: message.sender_id,
+            preview: preview(message.content),
+            sent_at: message.sent_at,
+        });
+
+        let other_profile_id = if summary.private_chat.first_profile_id == Some(*profile_id) {
+            summary.private_chat.second_profile_id
+        } else {
+            summary.private_chat.first_profile_id
+        };
+
+        GetInboxEntry {
+            kind: ChatKind::PrivateChat,
+            chat_id: summary.private_chat.private_chat_id,
+            name: None,
+            other_profile_id,
+            last_activity: last_message
+                .as_ref()
+                .map_or(summary.private_chat.creation_date, |message| {
+                    message.sent_at
+                }),
+            last_message,
+            unread_count: summary.unread_count,
+        }
+    });
+
+    let mut entries: Vec<GetInboxEntry> = group_chat_entries.chain(private_chat_entries).collect();
+    entries.sort_by_key(|entry| Reverse(entry.last_activity));
+
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+pub fn inbox_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_inbox);
+}
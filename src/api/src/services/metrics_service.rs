@@ -0,0 +1,38 @@
+use crate::error::*;
+use crate::AppState;
+use actix_web::*;
+use database::*;
+use log::*;
+
+/// Get metrics
+///
+/// Retrieve the metrics of this instance in the Prometheus text format
+#[utoipa::path(
+    tag = "Health",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain; version=0.0.4"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/metrics")]
+pub(super) async fn metrics(data: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    match count_active_sessions(db_connection).await {
+        Ok(active_sessions) => data.metrics.active_sessions.set(active_sessions as i64),
+        Err(err) => warn!("R: Unable to count the active sessions: {}", err),
+    }
+
+    let metrics = data.metrics.encode().map_err(|err| {
+        error!("Unable to encode the metrics: {}", err);
+        ApiError::internal()
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics))
+}
+
+pub fn metrics_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(metrics);
+}
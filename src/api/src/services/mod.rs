@@ -1,5 +1,14 @@
+pub mod auth_service;
+pub mod export_service;
+pub mod gateway_service;
+pub mod group_chat_invite_service;
+pub mod group_chat_join_request_service;
 pub mod group_chat_members_service;
 pub mod group_chat_message_service;
 pub mod group_chat_service;
+pub mod health_service;
+pub mod inbox_service;
+pub mod metrics_service;
+pub mod private_chat_service;
 pub mod private_message_service;
 pub mod profile_service;
@@ -0,0 +1,236 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use crate::api_models::pagination_schema::PageQuery;
+use crate::api_models::private_chat_schema::*;
+use crate::api_models::private_message_schema::*;
+use crate::api_models::read_state_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::pagination::*;
+use crate::policy::*;
+use crate::AppState;
+use actix_web::*;
+use database::sea_orm::DbConn;
+use database::*;
+use entities::{private_chat, profile};
+
+/// Loads a private chat, which the caller has to participate in.
+async fn find_private_chat_of_caller(
+    caller: &profile::Model,
+    private_chat_id: i32,
+    connection: &DbConn,
+) -> Result<private_chat::Model, ApiError> {
+    let private_chat = get_private_chat_by_id(private_chat_id, connection).await?;
+
+    if !is_private_chat_participant(
+        caller,
+        private_chat.first_profile_id,
+        private_chat.second_profile_id,
+    ) {
+        return Err(ApiError::not_private_chat_participant());
+    }
+
+    Ok(private_chat)
+}
+
+/// Get private chat
+///
+/// Get a specific private chat by its identifier
+#[utoipa::path(
+    tag = "Private Chat",
+    params(
+        ("private_chat_id", description = "Identifier of private chat")
+    ),
+    responses(
+        (status = 200, body = GetPrivateChat),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private chat", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/private_chat/{private_chat_id}")]
+pub(super) async fn get_private_chat(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    private_chat_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let private_chat =
+        find_private_chat_of_caller(&caller.profile, private_chat_id.to_owned(), db_connection)
+            .await?;
+
+    Ok(HttpResponse::Ok().json(GetPrivateChat::from(private_chat)))
+}
+
+/// Get messages of private chat
+///
+/// Retrieve a page of the messages of both participants of a private chat. Messages are ordered
+/// chronologically, `next_cursor` continues in the requested direction.
+#[utoipa::path(
+    tag = "Private Chat",
+    params(
+        ("private_chat_id", description = "Identifier of private chat"),
+        PageQuery
+    ),
+    responses(
+        (status = 200, body = GetPrivateMessagePage),
+        (status = 400, description = "Invalid cursor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private chat", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/private_chat/{private_chat_id}/messages")]
+pub(super) async fn get_private_chat_history(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    private_chat_id: web::Path<i32>,
+    page_query: web::Query<PageQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let private_chat =
+        find_private_chat_of_caller(&caller.profile, private_chat_id.to_owned(), db_connection)
+            .await?;
+
+    let position = message_page_position(&page_query)
+        .ok_or_else(|| ApiError::bad_request("invalid_cursor", "The cursor is invalid."))?;
+
+    let page = get_messages_of_private_chat_page(
+        private_chat.private_chat_id,
+        position,
+        page_size(page_query.limit),
+        db_connection,
+    )
+    .await?;
+
+    let next_cursor = next_cursor(&page.messages, page.has_more, &position, |message| {
+        encode_message_cursor(message.sent_at, message.private_message_id)
+    });
+
+    Ok(HttpResponse::Ok().json(GetPrivateMessagePage {
+        messages: page
+            .messages
+            .into_iter()
+            .map(|message| GetPrivateMessage::from(message).with_read_state(&private_chat))
+            .collect(),
+        next_cursor,
+    }))
+}
+
+/// Get private chats of profile
+///
+/// Retrieve all private chats a specific profile participates in, newest first
+#[utoipa::path(
+    tag = "Private Chat",
+    params(
+        ("profile_id", description = "Identifier of profile")
+    ),
+    responses(
+        (status = 200, body = [GetPrivateChat]),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to access this profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/profile/{profile_id}/private_chats")]
+pub(super) async fn get_profile_private_chats(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    profile_id: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    if !can_manage_profile(&caller.profile, profile_id.to_owned()) {
+        return Err(ApiError::forbidden(
+            "profile_access_denied",
+            "You are not allowed to access this profile.",
+        ));
+    }
+
+    let private_chats: Vec<GetPrivateChat> =
+        get_private_chats_of_profile(profile_id.to_owned(), db_connection)
+            .await?
+            .into_iter()
+            .map(GetPrivateChat::from)
+            .collect();
+
+    Ok(HttpResponse::Ok().json(private_chats))
+}
+
+/// Mark private chat as read
+///
+/// Advance the read cursor of the caller in a specific private chat up to the given message, or
+/// the newest message if none is given. The cursor never moves backwards.
+#[utoipa::path(
+    tag = "Private Chat",
+    request_body = PostReadState,
+    params(
+        ("private_chat_id", description = "Identifier of private chat")
+    ),
+    responses(
+        (status = 200, body = GetReadState),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private chat or message", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/private_chat/{private_chat_id}/read")]
+pub(super) async fn read_private_chat(
+    data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
+    private_chat_id: web::Path<i32>,
+    read_state: web::Json<PostReadState>,
+) -> Result<HttpResponse, ApiError> {
+    let db_connection = &data.db_connection;
+
+    let private_chat =
+        find_private_chat_of_caller(&caller.profile, private_chat_id.to_owned(), db_connection)
+            .await?;
+
+    let private_chat = mark_private_chat_read(
+        caller.profile.profile_id,
+        private_chat.private_chat_id,
+        read_state.message_id,
+        db_connection,
+    )
+    .await?;
+
+    let read_state = GetReadState {
+        chat_id: private_chat.private_chat_id,
+        profile_id: caller.profile.profile_id,
+        last_read_message_id: private_chat_last_read_message_id(
+            &private_chat,
+            caller.profile.profile_id,
+        ),
+    };
+
+    data.event_broker.publish_to_participants(
+        [
+            private_chat.first_profile_id,
+            private_chat.second_profile_id,
+        ],
+        &GatewayEvent::PrivateChatRead(read_state.clone()),
+    );
+
+    Ok(HttpResponse::Ok().json(read_state))
+}
+
+pub fn private_chat_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_private_chat);
+    cfg.service(get_private_chat_history);
+    cfg.service(get_profile_private_chats);
+    cfg.service(read_private_chat);
+}
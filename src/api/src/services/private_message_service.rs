@@ -1,4 +1,10 @@
+use crate::api_models::gateway_event_schema::GatewayEvent;
+use crate::api_models::pagination_schema::PageQuery;
 use crate::api_models::private_message_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::pagination::*;
+use crate::policy::*;
 use crate::AppState;
 use actix_web::*;
 use database::*;
@@ -11,28 +17,41 @@ use database::*;
     request_body = PostPrivateMessage,
     responses(
         (status = 201, description = "Success!"),
-        (status = 500, description = "Error!")
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified recipient", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[post("/private_message/new")]
 pub(super) async fn new_private_message(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     new_private_message: web::Json<PostPrivateMessage>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let result = insert_private_message(
-        new_private_message.sender_id.to_owned(),
+    let message = insert_private_message(
+        caller.profile.profile_id,
         new_private_message.recipient_id.to_owned(),
         new_private_message.content.to_owned(),
-        &db_connection,
+        db_connection,
     )
-    .await;
+    .await?;
 
-    match result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::InternalServerError().body("Error!"),
-    }
+    data.metrics
+        .messages_sent
+        .with_label_values(&["private_chat"])
+        .inc();
+
+    data.event_broker.publish_to_participants(
+        [message.sender_id, message.recipient_id],
+        &GatewayEvent::PrivateMessageCreated(message.into()),
+    );
+
+    Ok(HttpResponse::Created().body("Success!"))
 }
 
 /// Get private message
@@ -44,76 +63,109 @@ pub(super) async fn new_private_message(
         ("private_message_id", description = "Identifier of private message")
     ),
     responses(
-        (status = 201, body = GetPrivateMessage),
-        (status = 404, description = "Couldn't find the specified private message!")
+        (status = 200, body = GetPrivateMessage),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private message", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/private_message/{private_message_id}")]
 pub(super) async fn get_private_message(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     private_message_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result =
-        get_private_message_by_id(private_message_id.to_owned(), &db_connection).await;
+    let private_message =
+        get_private_message_by_id(private_message_id.to_owned(), db_connection).await?;
 
-    match query_result {
-        Ok(private_message) => {
-            let message_schema = GetPrivateMessage {
-                sender_id: private_message.sender_id,
-                recipient_id: private_message.recipient_id,
-                content: private_message.content,
-            };
-
-            HttpResponse::Ok().json(message_schema)
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified private message!"),
+    if !is_private_chat_participant(
+        &caller.profile,
+        private_message.sender_id,
+        private_message.recipient_id,
+    ) {
+        return Err(ApiError::not_private_chat_participant());
     }
+
+    Ok(HttpResponse::Ok().json(GetPrivateMessage::from(private_message)))
 }
 
-/// Get all private messages of private chat
+/// Get private messages of private chat
 ///
-/// Retrieve all private messages of a specific private chat using post data
+/// Retrieve a page of the messages of both participants of a private chat using post data.
+/// Messages are ordered chronologically, `next_cursor` continues in the requested direction.
 #[utoipa::path(
     tag = "Private Message",
     request_body = DeletePostPrivateChat,
+    params(
+        PageQuery
+    ),
     responses(
-        (status = 201, body = [GetPrivateMessage]),
-        (status = 404, description = "Couldn't find the specified private message!")
+        (status = 200, body = GetPrivateMessagePage),
+        (status = 400, description = "Invalid cursor", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[post("/private_message/chat")]
 pub(super) async fn get_private_chat_messages(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     private_chat: web::Json<DeletePostPrivateChat>,
-) -> impl Responder {
+    page_query: web::Query<PageQuery>,
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_private_messages_of_chat(
+    if !is_private_chat_participant(
+        &caller.profile,
+        Some(private_chat.sender_id),
+        Some(private_chat.recipient_id),
+    ) {
+        return Err(ApiError::not_private_chat_participant());
+    }
+
+    let position = message_page_position(&page_query)
+        .ok_or_else(|| ApiError::bad_request("invalid_cursor", "The cursor is invalid."))?;
+
+    let page = get_private_messages_of_chat_page(
         private_chat.sender_id.to_owned(),
         private_chat.recipient_id.to_owned(),
-        &db_connection,
+        position,
+        page_size(page_query.limit),
+        db_connection,
     )
-    .await;
+    .await?;
 
-    if query_result.is_err() {
-        return HttpResponse::NotFound().body("Couldn't find the specified private chat!");
-    }
+    let next_cursor = next_cursor(&page.messages, page.has_more, &position, |message| {
+        encode_message_cursor(message.sent_at, message.private_message_id)
+    });
 
-    let mut messages: Vec<GetPrivateMessage> = vec![];
-
-    for message in query_result.unwrap() {
-        let message_schema = GetPrivateMessage {
-            sender_id: message.sender_id,
-            recipient_id: message.recipient_id,
-            content: message.content,
-        };
-
-        messages.push(message_schema);
-    }
+    let read_state = get_private_chat_of_participants(
+        private_chat.sender_id,
+        private_chat.recipient_id,
+        db_connection,
+    )
+    .await
+    .unwrap_or_default();
 
-    HttpResponse::Ok().json(messages)
+    Ok(HttpResponse::Ok().json(GetPrivateMessagePage {
+        messages: page
+            .messages
+            .into_iter()
+            .map(|message| match &read_state {
+                Some(read_state) => GetPrivateMessage::from(message).with_read_state(read_state),
+                None => GetPrivateMessage::from(message),
+            })
+            .collect(),
+        next_cursor,
+    }))
 }
 
 /// Update private message
@@ -126,38 +178,49 @@ pub(super) async fn get_private_chat_messages(
         ("private_message_id", description = "Identifier of private message")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified private message!"),
-        (status = 500, description = "Failed!")
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this private message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Deleted messages cannot be edited", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[patch("/private_message/{private_message_id}")]
 pub(super) async fn update_private_message(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     updated_fields: web::Json<PatchPrivateMessage>,
     private_message_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result =
-        get_private_message_by_id(private_message_id.to_owned(), &db_connection).await;
-
-    match query_result {
-        Ok(private_message) => {
-            let update_result = database::update_private_message(
-                private_message.private_message_id,
-                updated_fields.content.to_owned(),
-                &db_connection,
-            )
-            .await;
-
-            match update_result {
-                Ok(_) => HttpResponse::Ok().body("Success!"),
-                Err(_) => HttpResponse::InternalServerError().body("Failed!"),
-            }
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified private message!"),
+    let private_message =
+        get_private_message_by_id(private_message_id.to_owned(), db_connection).await?;
+
+    if !can_manage_message(&caller.profile, private_message.sender_id) {
+        return Err(ApiError::forbidden(
+            "message_access_denied",
+            "You are not allowed to modify this private message.",
+        ));
     }
+
+    let message = database::update_private_message(
+        private_message.private_message_id,
+        updated_fields.content.to_owned(),
+        db_connection,
+    )
+    .await?;
+
+    data.event_broker.publish_to_participants(
+        [message.sender_id, message.recipient_id],
+        &GatewayEvent::PrivateMessageUpdated(message.into()),
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete private message
@@ -169,55 +232,96 @@ pub(super) async fn update_private_message(
         ("private_message_id", description = "Identifier of private message")
     ),
     responses(
-        (status = 201, description = "Success!"),
-        (status = 500, description = "Error!")
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this private message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified private message", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/private_message/delete/{private_message_id}")]
 pub(super) async fn delete_private_message(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     private_message_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result =
-        delete_private_message_by_id(private_message_id.to_owned(), &db_connection).await;
+    let private_message =
+        get_private_message_by_id(private_message_id.to_owned(), db_connection).await?;
 
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::InternalServerError().body("Error!"),
+    if !can_manage_message(&caller.profile, private_message.sender_id) {
+        return Err(ApiError::forbidden(
+            "message_access_denied",
+            "You are not allowed to modify this private message.",
+        ));
     }
+
+    delete_private_message_by_id(private_message_id.to_owned(), db_connection).await?;
+
+    data.event_broker.publish_to_participants(
+        [private_message.sender_id, private_message.recipient_id],
+        &GatewayEvent::PrivateMessageDeleted {
+            private_message_id: private_message.private_message_id,
+        },
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete all private messages of private chat
 ///
-/// Delete all private message of a specific private chat by its identifier
+/// Delete the messages of both participants of a specific private chat
 #[utoipa::path(
     tag = "Private Message",
     request_body = DeletePostPrivateChat,
     responses(
-        (status = 201, description = "Success!"),
-        (status = 500, description = "Error!")
+        (status = 200, description = "Success!"),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not a participant of this private chat", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/private_message/chat/delete")]
 pub(super) async fn delete_private_chat_messages(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     private_chat: web::Json<DeletePostPrivateChat>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_private_messages_of_chat(
+    if !caller.profile.is_admin
+        && !is_private_chat_participant(
+            &caller.profile,
+            Some(private_chat.sender_id),
+            Some(private_chat.recipient_id),
+        )
+    {
+        return Err(ApiError::not_private_chat_participant());
+    }
+
+    delete_private_messages_of_chat(
         private_chat.sender_id.to_owned(),
         private_chat.recipient_id.to_owned(),
-        &db_connection,
+        db_connection,
     )
-    .await;
+    .await?;
 
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::InternalServerError().body("Error!"),
-    }
+    data.event_broker.publish(
+        &[private_chat.sender_id, private_chat.recipient_id],
+        &GatewayEvent::PrivateChatDeleted {
+            sender_id: private_chat.sender_id,
+            recipient_id: private_chat.recipient_id,
+        },
+    );
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 pub fn private_message_config(cfg: &mut web::ServiceConfig) {
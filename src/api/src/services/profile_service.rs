@@ -1,7 +1,10 @@
+use crate::api_models::erasure_schema::*;
 use crate::api_models::profile_schema::*;
+use crate::authentication::AuthenticatedProfile;
+use crate::error::*;
+use crate::policy::*;
 use crate::AppState;
 use actix_web::*;
-use database::sea_orm::DbErr;
 use database::*;
 
 #[get("/")]
@@ -16,34 +19,32 @@ async fn index() -> impl Responder {
     request_body = PostProfile,
     responses(
         (status = 201, description = "Success!"),
-        (status = 403, description = "Whitespaces cannot be used in usernames!"),
-        (status = 500, description = "Error!")
+        (status = 409, description = "The username is already taken", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Whitespaces cannot be used in usernames", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
 #[post("/profile/new")]
 pub(super) async fn new_profile(
     data: web::Data<AppState>,
     new_profile: web::Json<PostProfile>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    // TODO: hash password
-    let result = insert_profile(
+    let hashed_password = hash_password(&new_profile.password, &data.password_config)?;
+
+    insert_profile(
         &new_profile.username,
         &new_profile.displayname,
-        &new_profile.password,
+        &hashed_password,
         &new_profile.email_address,
-        &db_connection,
+        db_connection,
     )
-    .await;
-
-    match result {
-        Ok(_) => HttpResponse::Created().body("Success!"),
-        Err(error) => match error {
-            DbErr::Custom(text) => HttpResponse::Forbidden().body(text),
-            _ => HttpResponse::InternalServerError().body("Error!"),
-        },
-    }
+    .await?;
+
+    data.metrics.profiles_created.inc();
+
+    Ok(HttpResponse::Created().body("Success!"))
 }
 
 /// Get profile by id
@@ -56,31 +57,29 @@ pub(super) async fn new_profile(
     ),
     responses(
         (status = 200, body = GetProfile),
-        (status = 404, description = "Couldn't find the specified profile!")
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified profile", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/profile/{profile_id}")]
 pub(super) async fn get_profile(
     data: web::Data<AppState>,
+    _caller: AuthenticatedProfile,
     profile_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_profile_by_id(profile_id.to_owned(), &db_connection).await;
-
-    match query_result {
-        Ok(profile) => {
-            let profile_schema = GetProfile {
-                username: profile.username,
-                displayname: profile.displayname.unwrap(),
-                email_address: profile.email_address,
-                join_datetime: profile.join_datetime,
-            };
+    let profile = get_profile_by_id(profile_id.to_owned(), db_connection).await?;
 
-            HttpResponse::Ok().json(profile_schema)
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified profile!"),
-    }
+    Ok(HttpResponse::Ok().json(GetProfile {
+        username: profile.username,
+        displayname: profile.displayname.unwrap_or_default(),
+        email_address: profile.email_address,
+        join_datetime: profile.join_datetime,
+    }))
 }
 
 /// Get profile by username
@@ -93,31 +92,29 @@ pub(super) async fn get_profile(
     ),
     responses(
         (status = 200, body = GetProfile),
-        (status = 404, description = "Couldn't find the specified profile!")
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified profile", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[get("/profile/username/{profile_username}")]
 pub(super) async fn get_profile_username(
     data: web::Data<AppState>,
+    _caller: AuthenticatedProfile,
     profile_username: web::Path<String>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_profile_by_username(&profile_username, &db_connection).await;
-
-    match query_result {
-        Ok(profile) => {
-            let profile_schema = GetProfile {
-                username: profile.username,
-                displayname: profile.displayname.unwrap(),
-                email_address: profile.email_address,
-                join_datetime: profile.join_datetime,
-            };
+    let profile = get_profile_by_username(&profile_username, db_connection).await?;
 
-            HttpResponse::Ok().json(profile_schema)
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified profile!"),
-    }
+    Ok(HttpResponse::Ok().json(GetProfile {
+        username: profile.username,
+        displayname: profile.displayname.unwrap_or_default(),
+        email_address: profile.email_address,
+        join_datetime: profile.join_datetime,
+    }))
 }
 
 /// Update profile
@@ -131,82 +128,110 @@ pub(super) async fn get_profile_username(
     ),
     responses(
         (status = 200, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified profile!")
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "The username is already taken", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Unexpected error", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[patch("/profile/{profile_id}")]
 pub(super) async fn update_profile(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     updated_fields: web::Json<PatchProfile>,
     profile_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let query_result = get_profile_by_id(profile_id.to_owned(), &db_connection).await;
-
-    match query_result {
-        Ok(profile) => {
-            let update_result = database::update_profile(
-                profile.profile_id,
-                &updated_fields
-                    .username
-                    .to_owned()
-                    .unwrap_or(profile.username),
-                &updated_fields
-                    .displayname
-                    .to_owned()
-                    .unwrap_or(profile.displayname.unwrap()),
-                &updated_fields
-                    .password
-                    .to_owned()
-                    .unwrap_or(profile.password),
-                &updated_fields
-                    .email_address
-                    .to_owned()
-                    .unwrap_or(profile.email_address),
-                &updated_fields
-                    .profile_picture
-                    .to_owned()
-                    .unwrap_or(profile.profile_picture.unwrap()),
-                &db_connection,
-            )
-            .await;
-
-            match update_result {
-                Ok(_) => HttpResponse::Ok().body("Success!"),
-                Err(_) => HttpResponse::NotFound().body("Failed!"),
-            }
-        }
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified profile!"),
+    if !can_manage_profile(&caller.profile, profile_id.to_owned()) {
+        return Err(ApiError::forbidden(
+            "profile_access_denied",
+            "You are not allowed to modify this profile.",
+        ));
     }
+
+    let profile = get_profile_by_id(profile_id.to_owned(), db_connection).await?;
+
+    let hashed_password = match &updated_fields.password {
+        Some(password) => hash_password(password, &data.password_config)?,
+        None => profile.password,
+    };
+
+    database::update_profile(
+        profile.profile_id,
+        &updated_fields
+            .username
+            .to_owned()
+            .unwrap_or(profile.username),
+        &updated_fields
+            .displayname
+            .to_owned()
+            .unwrap_or(profile.displayname.unwrap_or_default()),
+        &hashed_password,
+        &updated_fields
+            .email_address
+            .to_owned()
+            .unwrap_or(profile.email_address),
+        &updated_fields
+            .profile_picture
+            .to_owned()
+            .unwrap_or(profile.profile_picture.unwrap_or_default()),
+        db_connection,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().body("Success!"))
 }
 
 /// Delete profile
 ///
-/// Delete s specific platform profile by its identifier
+/// Erase a specific platform profile by its identifier. Memberships and sessions of the profile are
+/// removed, its messages are anonymized or deleted depending on the configured erasure policy.
 #[utoipa::path(
     tag = "Profile",
     params(
         ("profile_id", description = "Identifier of profile")
     ),
     responses(
-        (status = 200, description = "Success!"),
-        (status = 404, description = "Couldn't find the specified profile!")
+        (status = 200, body = GetErasureAudit),
+        (status = 401, description = "Invalid or expired token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "You are not allowed to modify this profile", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Couldn't find the specified profile", body = ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(
+        ("bearer_token" = [])
     )
 )]
 #[delete("/profile/{profile_id}")]
 pub(super) async fn delete_profile(
     data: web::Data<AppState>,
+    caller: AuthenticatedProfile,
     profile_id: web::Path<i32>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let db_connection = &data.db_connection;
 
-    let delete_result = delete_profile_by_id(profile_id.to_owned(), &db_connection).await;
-
-    match delete_result {
-        Ok(_) => HttpResponse::Ok().body("Success!"),
-        Err(_) => HttpResponse::NotFound().body("Couldn't find the specified profile!"),
+    if !can_manage_profile(&caller.profile, profile_id.to_owned()) {
+        return Err(ApiError::forbidden(
+            "profile_access_denied",
+            "You are not allowed to modify this profile.",
+        ));
     }
+
+    let audit_entry = erase_profile(
+        profile_id.to_owned(),
+        caller.profile.profile_id,
+        data.erasure_policy,
+        db_connection,
+    )
+    .await?;
+
+    data.event_broker.disconnect_profile(profile_id.to_owned());
+
+    Ok(HttpResponse::Ok().json(GetErasureAudit::from(audit_entry)))
 }
 
 pub fn profile_config(cfg: &mut web::ServiceConfig) {
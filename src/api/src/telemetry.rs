@@ -0,0 +1,74 @@
+use crate::config::TelemetryConfig;
+use actix_web::http::header::HeaderMap;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TracerProvider;
+use opentelemetry::Context;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::io;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// Reads the trace context of the `traceparent` and `tracestate` headers, so that the spans of a
+/// request continue the trace of the client. The context is empty if the headers are missing or
+/// invalid.
+pub fn extract_trace_context(headers: &HeaderMap) -> Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
+
+/// Creates the tracer provider which exports spans in batches to `endpoint` via OTLP/HTTP.
+pub fn otlp_tracer_provider(
+    telemetry_config: &TelemetryConfig,
+    endpoint: &str,
+) -> io::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(io::Error::other)?;
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        telemetry_config.sample_ratio,
+    )));
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(sampler)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(telemetry_config.service_name.clone())
+                .build(),
+        )
+        .build())
+}
+
+/// Turns the spans of the `api` and `database` crates into OpenTelemetry spans of
+/// `tracer_provider`. Their log records are attached to the spans as events.
+pub fn trace_layer<S>(tracer_provider: &SdkTracerProvider) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer_provider.tracer("sorume-server"))
+        .with_filter(
+            Targets::new()
+                .with_target("api", Level::INFO)
+                .with_target("database", Level::INFO),
+        )
+}
@@ -0,0 +1,203 @@
+use actix_web::http::header;
+use actix_web::http::uri::Authority;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{info, warn};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::{self, CertifiedKey};
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn load_certificates(certificate_path: &Path) -> io::Result<Vec<Certificate>> {
+    let mut certificate_reader = BufReader::new(File::open(certificate_path)?);
+    let certificates: Vec<Certificate> = rustls_pemfile::certs(&mut certificate_reader)?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    if certificates.is_empty() {
+        return Err(invalid_data(format!(
+            "{} contains no certificates",
+            certificate_path.display()
+        )));
+    }
+
+    Ok(certificates)
+}
+
+fn load_private_key(private_key_path: &Path) -> io::Result<PrivateKey> {
+    let mut private_key_reader = BufReader::new(File::open(private_key_path)?);
+
+    loop {
+        match rustls_pemfile::read_one(&mut private_key_reader)? {
+            Some(Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key)) => {
+                return Ok(PrivateKey(key))
+            }
+            Some(_) => continue,
+            None => {
+                return Err(invalid_data(format!(
+                    "{} contains no private key",
+                    private_key_path.display()
+                )))
+            }
+        }
+    }
+}
+
+/// Reads a PEM certificate chain and a PEM private key.
+fn load_certified_key(
+    certificate_path: &Path,
+    private_key_path: &Path,
+) -> io::Result<Arc<CertifiedKey>> {
+    let certificates = load_certificates(certificate_path)?;
+    let private_key = load_private_key(private_key_path)?;
+
+    let signing_key = sign::any_supported_type(&private_key).map_err(|_| {
+        invalid_data(format!(
+            "{} contains an unsupported private key",
+            private_key_path.display()
+        ))
+    })?;
+
+    Ok(Arc::new(CertifiedKey::new(certificates, signing_key)))
+}
+
+/// Serves the certificate of the HTTPS listener and allows replacing it while the server runs,
+/// e.g. after a renewal.
+pub struct CertificateResolver {
+    certificate_path: PathBuf,
+    private_key_path: PathBuf,
+    certified_key: RwLock<Arc<CertifiedKey>>,
+}
+
+impl CertificateResolver {
+    pub fn load(certificate_path: &Path, private_key_path: &Path) -> io::Result<Self> {
+        let certified_key = load_certified_key(certificate_path, private_key_path)?;
+
+        Ok(CertificateResolver {
+            certificate_path: certificate_path.to_path_buf(),
+            private_key_path: private_key_path.to_path_buf(),
+            certified_key: RwLock::new(certified_key),
+        })
+    }
+
+    /// Reads the certificate and the private key again. The current certificate is kept if they
+    /// can't be loaded.
+    pub fn reload(&self) -> io::Result<()> {
+        let certified_key = load_certified_key(&self.certificate_path, &self.private_key_path)?;
+
+        *self.certified_key.write().unwrap() = certified_key;
+
+        Ok(())
+    }
+
+    pub fn current_certificate(&self) -> Certificate {
+        self.certified_key.read().unwrap().cert[0].clone()
+    }
+
+    fn modification_time(&self) -> Option<(SystemTime, SystemTime)> {
+        let certificate_time = fs::metadata(&self.certificate_path).ok()?.modified().ok()?;
+        let private_key_time = fs::metadata(&self.private_key_path).ok()?.modified().ok()?;
+
+        Some((certificate_time, private_key_time))
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.read().unwrap().clone())
+    }
+}
+
+pub fn server_config(resolver: Arc<CertificateResolver>) -> ServerConfig {
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver)
+}
+
+fn reload_certificates(resolver: &CertificateResolver, trigger: &str) {
+    match resolver.reload() {
+        Ok(()) => info!("U: TLS certificate has been reloaded after {}", trigger),
+        Err(err) => warn!("U: Unable to reload the TLS certificate: {}", err),
+    }
+}
+
+/// Reloads the certificate on SIGHUP and, if `poll_interval` is set, whenever the modification
+/// time of the certificate or the private key changes.
+pub fn watch_certificates(resolver: Arc<CertificateResolver>, poll_interval: Option<Duration>) {
+    #[cfg(unix)]
+    {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+
+        let resolver = resolver.clone();
+
+        match signal(SignalKind::hangup()) {
+            Ok(mut hangups) => {
+                actix_web::rt::spawn(async move {
+                    while hangups.recv().await.is_some() {
+                        reload_certificates(&resolver, "SIGHUP");
+                    }
+                });
+            }
+            Err(err) => warn!("Unable to listen for SIGHUP: {}", err),
+        }
+    }
+
+    if let Some(poll_interval) = poll_interval {
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(poll_interval);
+            let mut last_modification = resolver.modification_time();
+
+            loop {
+                interval.tick().await;
+
+                let modification = resolver.modification_time();
+                if modification.is_some() && modification != last_modification {
+                    last_modification = modification;
+                    reload_certificates(&resolver, "a file change");
+                }
+            }
+        });
+    }
+}
+
+/// Answers every request with a permanent redirect to the same path on the HTTPS listener.
+pub fn redirect_config(https_port: u16) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.default_service(web::to(move |request: HttpRequest| async move {
+            let connection_info = request.connection_info();
+            let host = connection_info
+                .host()
+                .parse::<Authority>()
+                .map(|authority| authority.host().to_owned())
+                .unwrap_or_default();
+
+            if host.is_empty() {
+                return HttpResponse::BadRequest().finish();
+            }
+
+            let path = request
+                .uri()
+                .path_and_query()
+                .map_or("/", |path_and_query| path_and_query.as_str());
+
+            let location = match https_port {
+                443 => format!("https://{}{}", host, path),
+                _ => format!("https://{}:{}{}", host, https_port, path),
+            };
+
+            HttpResponse::PermanentRedirect()
+                .insert_header((header::LOCATION, location))
+                .finish()
+        }));
+    }
+}
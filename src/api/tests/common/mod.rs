@@ -0,0 +1,226 @@
+#![allow(dead_code)]
+
+use actix_http::Request;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use actix_web::{middleware, web, App, Error};
+use api::config::{Config, LimitsConfig};
+use api::metrics::record_request_metrics;
+use api::request_id::assign_request_id;
+use api::{api_config, AppState};
+use database::sea_orm::*;
+use database::{get_profile_by_id, get_profile_by_username, ErasurePolicy};
+use entities::profile;
+use migration::{Migrator, MigratorTrait};
+use serde_json::{json, Value};
+
+pub const PASSWORD: &str = "correct horse battery staple";
+
+/// Profile which has been created and logged in through the API.
+pub struct TestProfile {
+    pub profile_id: i32,
+    pub username: String,
+    pub access_token: String,
+}
+
+impl TestProfile {
+    pub fn bearer(&self) -> (header::HeaderName, String) {
+        (
+            header::AUTHORIZATION,
+            format!("Bearer {}", self.access_token),
+        )
+    }
+}
+
+/// Application under test, backed by its own in-memory SQLite database.
+pub struct TestApp<S> {
+    pub service: S,
+    pub db_connection: DatabaseConnection,
+}
+
+pub async fn spawn_app() -> TestApp<impl Service<Request, Response = ServiceResponse, Error = Error>>
+{
+    spawn_app_with_policy(ErasurePolicy::Anonymize).await
+}
+
+pub async fn spawn_app_with_policy(
+    erasure_policy: ErasurePolicy,
+) -> TestApp<impl Service<Request, Response = ServiceResponse, Error = Error>> {
+    // every connection to `sqlite::memory:` opens a separate database, SeaORM limits the pool to
+    // a single connection for it
+    let db_connection = Database::connect("sqlite::memory:").await.unwrap();
+    Migrator::up(&db_connection, None).await.unwrap();
+
+    let mut config = Config::default();
+    config.erasure.policy = erasure_policy;
+
+    let data = web::Data::new(AppState::new(db_connection.clone(), &config));
+    let service = test::init_service(
+        App::new()
+            .app_data(data)
+            .wrap(middleware::from_fn(record_request_metrics))
+            .wrap(middleware::from_fn(assign_request_id))
+            .configure(api_config(LimitsConfig::default())),
+    )
+    .await;
+
+    TestApp {
+        service,
+        db_connection,
+    }
+}
+
+impl<S> TestApp<S>
+where
+    S: Service<Request, Response = ServiceResponse, Error = Error>,
+{
+    pub async fn call(&self, request: TestRequest) -> ServiceResponse {
+        test::call_service(&self.service, request.to_request()).await
+    }
+
+    /// Sends the request and returns the status together with the JSON body.
+    pub async fn call_json(&self, request: TestRequest) -> (StatusCode, Value) {
+        let response = self.call(request).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    pub async fn register(&self, username: &str) -> TestProfile {
+        let response = self
+            .call(TestRequest::post().uri("/profile/new").set_json(json!({
+                "username": username,
+                "displayname": null,
+                "password": PASSWORD,
+                "email_address": format!("{}@example.org", username),
+            })))
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let profile = get_profile_by_username(username, &self.db_connection)
+            .await
+            .unwrap();
+
+        TestProfile {
+            profile_id: profile.profile_id,
+            username: username.to_owned(),
+            access_token: self.login(username).await,
+        }
+    }
+
+    pub async fn login(&self, username: &str) -> String {
+        let (status, session) = self
+            .call_json(TestRequest::post().uri("/auth/login").set_json(json!({
+                "username": username,
+                "password": PASSWORD,
+            })))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        session["access_token"].as_str().unwrap().to_owned()
+    }
+
+    pub async fn register_admin(&self, username: &str) -> TestProfile {
+        let admin = self.register(username).await;
+
+        let mut admin_profile: profile::ActiveModel =
+            get_profile_by_id(admin.profile_id, &self.db_connection)
+                .await
+                .unwrap()
+                .into();
+        admin_profile.is_admin = Set(true);
+        admin_profile.update(&self.db_connection).await.unwrap();
+
+        admin
+    }
+
+    /// Creates a group chat of `owner` with the given members and returns its identifier.
+    pub async fn create_group_chat(&self, owner: &TestProfile, members: &[&TestProfile]) -> i32 {
+        let member_ids: Vec<i32> = members.iter().map(|member| member.profile_id).collect();
+
+        let (status, group_chat) = self
+            .call_json(
+                TestRequest::post()
+                    .uri("/group_chat/new")
+                    .insert_header(owner.bearer())
+                    .set_json(json!({
+                        "name": "Test group",
+                        "description": null,
+                        "member_ids": member_ids,
+                        "group_picture": null,
+                    })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        group_chat["group_chat_id"].as_i64().unwrap() as i32
+    }
+
+    /// Creates an invite of `caller` for the group chat with the given limits and returns it.
+    pub async fn create_invite(
+        &self,
+        caller: &TestProfile,
+        group_chat_id: i32,
+        limits: Value,
+    ) -> Value {
+        let (status, invite) = self
+            .call_json(
+                TestRequest::post()
+                    .uri(&format!("/group_chat/{}/invites/new", group_chat_id))
+                    .insert_header(caller.bearer())
+                    .set_json(limits),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        invite
+    }
+
+    pub async fn send_group_message(
+        &self,
+        author: &TestProfile,
+        group_chat_id: i32,
+        content: &str,
+    ) {
+        let response = self
+            .call(
+                TestRequest::post()
+                    .uri(&format!("/group_chat/{}/messages/new", group_chat_id))
+                    .insert_header(author.bearer())
+                    .set_json(json!({ "content": content })),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    pub async fn send_private_message(
+        &self,
+        sender: &TestProfile,
+        recipient_id: i32,
+        content: &str,
+    ) {
+        let response = self
+            .call(
+                TestRequest::post()
+                    .uri("/private_message/new")
+                    .insert_header(sender.bearer())
+                    .set_json(json!({ "recipient_id": recipient_id, "content": content })),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+}
+
+/// Asserts that the response is a problem document with the given status and code.
+pub async fn assert_problem(response: ServiceResponse, status: StatusCode, code: &str) {
+    assert_eq!(response.status(), status);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/problem+json"
+    );
+
+    let problem: Value = test::read_body_json(response).await;
+    assert_eq!(problem["code"], code, "unexpected problem: {}", problem);
+}
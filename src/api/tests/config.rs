@@ -0,0 +1,95 @@
+use api::config::{Config, ConfigError};
+use database::ErasurePolicy;
+use std::path::{Path, PathBuf};
+
+fn example_config_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../sorume.example.toml")
+}
+
+fn valid_config() -> Config {
+    let mut config = Config::default();
+    config.database.url = "sqlite::memory:".to_owned();
+    config
+}
+
+fn assert_invalid(mut config: Config, setting: &str) {
+    match config.validate() {
+        Err(ConfigError::Invalid(reason)) => assert!(reason.contains(setting), "{}", reason),
+        other => panic!("expected {} to be rejected, got {:?}", setting, other),
+    }
+}
+
+#[test]
+fn example_config_is_valid() {
+    let mut config = Config::from_file(&example_config_path()).unwrap();
+    config.validate().unwrap();
+
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.database.max_connections, 10);
+    assert!(!config.tls.is_enabled());
+    assert_eq!(config.session.lifetime_hours, 24);
+    assert_eq!(config.erasure.policy, ErasurePolicy::Anonymize);
+}
+
+#[test]
+fn unknown_settings_are_rejected() {
+    let path = std::env::temp_dir().join("sorume-unknown-setting.toml");
+    std::fs::write(&path, "[server]\nprot = 8080\n").unwrap();
+
+    let err = Config::from_file(&path).unwrap_err();
+    assert!(matches!(err, ConfigError::Parse { .. }));
+    assert!(err.to_string().contains("prot"), "{}", err);
+
+    let err = Config::from_file(Path::new("missing.toml")).unwrap_err();
+    assert!(matches!(err, ConfigError::Read { .. }));
+}
+
+#[test]
+fn invalid_settings_are_rejected() {
+    valid_config().validate().unwrap();
+
+    let mut config = valid_config();
+    config.server.port = 0;
+    assert_invalid(config, "server.port");
+
+    let mut config = valid_config();
+    config.server.workers = Some(0);
+    assert_invalid(config, "server.workers");
+
+    let mut config = valid_config();
+    config.database.min_connections = 20;
+    assert_invalid(config, "database.min_connections");
+
+    let mut config = valid_config();
+    config.limits.max_json_body_size = 0;
+    assert_invalid(config, "limits.max_json_body_size");
+
+    let mut config = valid_config();
+    config.telemetry.otlp_endpoint = Some("localhost:4318".to_owned());
+    assert_invalid(config, "telemetry.otlp_endpoint");
+
+    let mut config = valid_config();
+    config.telemetry.sample_ratio = 1.5;
+    assert_invalid(config, "telemetry.sample_ratio");
+
+    let mut config = valid_config();
+    config.password.parallelism = 0;
+    assert_invalid(config, "password.parallelism");
+
+    let mut config = valid_config();
+    config.session.lifetime_hours = 0;
+    assert_invalid(config, "session.lifetime_hours");
+
+    let mut config = valid_config();
+    config.tls.certificate_path = Some(example_config_path());
+    assert_invalid(config, "tls.private_key_path");
+
+    let mut config = valid_config();
+    config.tls.redirect_port = Some(8081);
+    assert_invalid(config, "tls.redirect_port");
+
+    let mut config = valid_config();
+    config.tls.certificate_path = Some(PathBuf::from("missing.pem"));
+    config.tls.private_key_path = Some(PathBuf::from("missing.pem"));
+    assert_invalid(config, "tls.certificate_path");
+}
@@ -0,0 +1,996 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use common::*;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn create_and_update_group_chat() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let (status, group_chat) = app
+        .call_json(
+            TestRequest::post()
+                .uri("/group_chat/new")
+                .insert_header(alice.bearer())
+                .set_json(json!({
+                    "name": "  Book club ",
+                    "description": "Monthly reading\n",
+                    "member_ids": [bob.profile_id, 999],
+                    "group_picture": "dawn",
+                })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(group_chat["rejected_member_ids"], json!([999]));
+    let group_chat_id = group_chat["group_chat_id"].as_i64().unwrap();
+
+    let (_, group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(group_chat["group_chat_id"], group_chat_id);
+    assert_eq!(group_chat["name"], "Book club");
+    assert_eq!(group_chat["description"], "Monthly reading");
+    assert_eq!(group_chat["created_by"], alice.profile_id);
+    assert_eq!(group_chat["group_picture"], "dawn");
+    assert_eq!(group_chat["updated_at"], group_chat["creation_date"]);
+    assert_eq!(
+        group_chat["members"],
+        json!([
+            { "profile_id": alice.profile_id, "role": "owner" },
+            { "profile_id": bob.profile_id, "role": "member" },
+        ])
+    );
+
+    // plain members can't edit the details
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "name": "Film club" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(
+                    json!({ "name": "Film club", "description": " ", "group_picture": "sunset" }),
+                ),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, updated_group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated_group_chat["name"], "Film club");
+    assert_eq!(updated_group_chat["description"], Value::Null);
+    assert_eq!(updated_group_chat["group_picture"], "sunset");
+    assert_eq!(updated_group_chat["created_by"], alice.profile_id);
+    assert_ne!(updated_group_chat["updated_at"], group_chat["updated_at"]);
+
+    // missing fields are kept
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "description": "Films instead" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(group_chat["name"], "Film club");
+    assert_eq!(group_chat["description"], "Films instead");
+    assert_eq!(group_chat["group_picture"], "sunset");
+}
+
+#[actix_web::test]
+async fn invalid_group_chat_details_are_rejected() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+
+    for name in ["", "   ", "x".repeat(65).as_str(), "two\nlines"] {
+        let response = app
+            .call(
+                TestRequest::post()
+                    .uri("/group_chat/new")
+                    .insert_header(alice.bearer())
+                    .set_json(json!({ "name": name, "member_ids": [], "group_picture": null })),
+            )
+            .await;
+        assert_problem(
+            response,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "validation_failed",
+        )
+        .await;
+
+        let response = app
+            .call(
+                TestRequest::patch()
+                    .uri(&format!("/group_chat/{}", group_chat_id))
+                    .insert_header(alice.bearer())
+                    .set_json(json!({ "name": name })),
+            )
+            .await;
+        assert_problem(
+            response,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "validation_failed",
+        )
+        .await;
+    }
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "description": "x".repeat(1025) })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+    )
+    .await;
+
+    // names are counted in characters, not bytes
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "name": "ü".repeat(64) })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the name is required
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/group_chat/new")
+                .insert_header(alice.bearer())
+                .set_json(json!({ "member_ids": [], "group_picture": null })),
+        )
+        .await;
+    assert_problem(response, StatusCode::BAD_REQUEST, "invalid_body").await;
+}
+
+#[actix_web::test]
+async fn group_chats_are_limited_to_members() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let eve = app.register("eve").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+    app.send_group_message(&alice, group_chat_id, "hello").await;
+
+    let requests = [
+        TestRequest::get().uri(&format!("/group_chat/{}", group_chat_id)),
+        TestRequest::patch()
+            .uri(&format!("/group_chat/{}", group_chat_id))
+            .set_json(json!({ "group_picture": "defaced" })),
+        TestRequest::delete().uri(&format!("/group_chat/delete/{}", group_chat_id)),
+        TestRequest::post()
+            .uri(&format!("/group_chat/{}/read", group_chat_id))
+            .set_json(json!({ "message_id": null })),
+        TestRequest::get().uri(&format!("/group_chat/{}/members", group_chat_id)),
+        TestRequest::post()
+            .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+            .set_json(json!({ "profile_id": eve.profile_id })),
+        TestRequest::delete().uri(&format!("/group_chat/{}/members", group_chat_id)),
+        TestRequest::get().uri(&format!("/group_chat/{}/messages", group_chat_id)),
+        TestRequest::post()
+            .uri(&format!("/group_chat/{}/messages/new", group_chat_id))
+            .set_json(json!({ "content": "intrusion" })),
+        TestRequest::get().uri(&format!(
+            "/group_chat/{}/members/{}/messages",
+            group_chat_id, alice.profile_id
+        )),
+    ];
+
+    for request in requests {
+        let response = app.call(request.insert_header(eve.bearer())).await;
+        assert_problem(response, StatusCode::FORBIDDEN, "not_group_chat_member").await;
+    }
+}
+
+#[actix_web::test]
+async fn missing_group_chat() {
+    let app = spawn_app().await;
+    let admin = app.register_admin("admin").await;
+
+    let requests = [
+        TestRequest::delete().uri("/group_chat/delete/999"),
+        TestRequest::delete().uri("/group_chat/999/members"),
+        TestRequest::delete().uri("/group_chat/999/messages"),
+        TestRequest::delete().uri(&format!(
+            "/group_chat/999/members/{}/messages",
+            admin.profile_id
+        )),
+    ];
+
+    for request in requests {
+        let response = app.call(request.insert_header(admin.bearer())).await;
+        assert_problem(response, StatusCode::NOT_FOUND, "group_chat_not_found").await;
+    }
+}
+
+#[actix_web::test]
+async fn delete_group_chat() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&bob, group_chat_id, "hello").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/delete/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, inbox) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/inbox", bob.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(inbox, json!([]));
+}
+
+#[actix_web::test]
+async fn read_group_chat() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "hello").await;
+
+    let (status, read_state) = app
+        .call_json(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/read", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "message_id": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(read_state["chat_id"], group_chat_id);
+    assert_eq!(read_state["profile_id"], bob.profile_id);
+    assert_eq!(read_state["last_read_message_id"], 1);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"][0]["seen_by"], 1);
+}
+
+#[actix_web::test]
+async fn manage_group_chat_members() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+
+    for profile in [&bob, &carol] {
+        let response = app
+            .call(
+                TestRequest::post()
+                    .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                    .insert_header(alice.bearer())
+                    .set_json(json!({ "profile_id": profile.profile_id })),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "profile_id": 999 })),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "profile_id": bob.profile_id })),
+        )
+        .await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let (status, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        members,
+        json!([
+            { "profile_id": alice.profile_id, "role": "owner" },
+            { "profile_id": bob.profile_id, "role": "member" },
+            { "profile_id": carol.profile_id, "role": "member" },
+        ])
+    );
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, carol.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "member_removal_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_member_not_found",
+    )
+    .await;
+
+    let (_, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(members.as_array().unwrap().len(), 2);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the owner stays, so that the group chat can still be managed
+    let (status, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        members,
+        json!([{ "profile_id": alice.profile_id, "role": "owner" }])
+    );
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(carol.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "not_group_chat_member").await;
+}
+
+#[actix_web::test]
+async fn group_chat_roles() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob, &carol]).await;
+    app.send_group_message(&carol, group_chat_id, "hello").await;
+
+    let set_role = |caller: &TestProfile, profile_id: i32, role: &str| {
+        TestRequest::patch()
+            .uri(&format!(
+                "/group_chat/{}/members/{}",
+                group_chat_id, profile_id
+            ))
+            .insert_header(caller.bearer())
+            .set_json(json!({ "role": role }))
+    };
+
+    // members can't add people, manage roles or delete others' messages
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "profile_id": dave.profile_id })),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app.call(set_role(&bob, bob.profile_id, "admin")).await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/1", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let (status, member) = app
+        .call_json(set_role(&alice, bob.profile_id, "admin"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        member,
+        json!({ "profile_id": bob.profile_id, "role": "admin" })
+    );
+
+    // admins moderate members, but not the owner, its messages or the group chat itself
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "profile_id": dave.profile_id })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/1", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, dave.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, alice.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "member_removal_denied").await;
+
+    app.send_group_message(&alice, group_chat_id, "from the owner")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/2", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}/messages",
+                    group_chat_id, alice.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/delete/{}", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    // the owner can't be demoted, only replaced
+    let response = app.call(set_role(&alice, alice.profile_id, "member")).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let response = app.call(set_role(&alice, carol.profile_id, "owner")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(
+        members,
+        json!([
+            { "profile_id": alice.profile_id, "role": "admin" },
+            { "profile_id": bob.profile_id, "role": "admin" },
+            { "profile_id": carol.profile_id, "role": "owner" },
+        ])
+    );
+
+    // when the owner leaves, the longest-standing admin takes over
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, carol.profile_id
+                ))
+                .insert_header(carol.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(
+        members,
+        json!([
+            { "profile_id": alice.profile_id, "role": "owner" },
+            { "profile_id": bob.profile_id, "role": "admin" },
+        ])
+    );
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/delete/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn site_admins_manage_group_chats_without_membership() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let admin = app.register_admin("admin").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/members/new", group_chat_id))
+                .insert_header(admin.bearer())
+                .set_json(json!({ "profile_id": bob.profile_id })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(admin.bearer())
+                .set_json(json!({ "name": "Moderated" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(group_chat["name"], "Moderated");
+    assert_eq!(
+        group_chat["members"],
+        json!([
+            { "profile_id": alice.profile_id, "role": "owner" },
+            { "profile_id": bob.profile_id, "role": "member" },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn send_and_page_group_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "first").await;
+    app.send_group_message(&bob, group_chat_id, "second").await;
+    app.send_group_message(&alice, group_chat_id, "third").await;
+
+    let (status, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages?limit=2", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["messages"][0]["content"], "second");
+    assert_eq!(page["messages"][1]["content"], "third");
+    assert!(page["messages"][0]["send_time"].is_string());
+
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/group_chat/{}/messages?limit=2&before={}",
+                    group_chat_id, cursor
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["content"], "first");
+    assert_eq!(page["next_cursor"], Value::Null);
+
+    // the cursor of "second" continues with the messages sent after it
+    let (status, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/group_chat/{}/messages?after={}",
+                    group_chat_id, cursor
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["content"], "third");
+    assert_eq!(page["next_cursor"], Value::Null);
+
+    let (status, messages) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}/messages",
+                    group_chat_id, alice.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let contents: Vec<&Value> = messages
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| &message["content"])
+        .collect();
+    assert_eq!(contents, [&json!("first"), &json!("third")]);
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/messages/new", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "content": null })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!(
+                    "/group_chat/{}/members/999/messages",
+                    group_chat_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+}
+
+#[actix_web::test]
+async fn update_and_delete_group_message() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    let other_group_chat_id = app.create_group_chat(&alice, &[]).await;
+    app.send_group_message(&alice, group_chat_id, "first").await;
+    app.send_group_message(&alice, group_chat_id, "second")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}/messages/1", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "content": "hijacked" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}/messages/1", other_group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "content": "misplaced" })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_message_not_found",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}/messages/1", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "content": "edited" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/2", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    // deleting the second message must leave the first one untouched
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/2", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["message_id"], 1);
+    assert_eq!(page["messages"][0]["content"], "edited");
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/message/999", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_message_not_found",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn delete_group_messages_of_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "from alice")
+        .await;
+    app.send_group_message(&bob, group_chat_id, "from bob")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}/messages",
+                    group_chat_id, alice.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}/messages",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["content"], "from alice");
+}
+
+#[actix_web::test]
+async fn delete_all_group_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let admin = app.register_admin("admin").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    let other_group_chat_id = app.create_group_chat(&alice, &[]).await;
+    app.send_group_message(&alice, group_chat_id, "first").await;
+    app.send_group_message(&alice, other_group_chat_id, "elsewhere")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(carol.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "not_group_chat_member").await;
+
+    // the owner and site administrators may clear the group chat
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    app.send_group_message(&bob, group_chat_id, "second").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(admin.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"], json!([]));
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", other_group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"][0]["content"], "elsewhere");
+}
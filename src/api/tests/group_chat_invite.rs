@@ -0,0 +1,424 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use common::*;
+use database::sea_orm::prelude::DateTime;
+use database::sea_orm::sea_query::Expr;
+use database::sea_orm::*;
+use entities::group_chat_invite;
+use serde_json::{json, Value};
+
+fn join(caller: &TestProfile, invite: &Value) -> TestRequest {
+    TestRequest::post()
+        .uri(&format!(
+            "/invite/{}/join",
+            invite["code"].as_str().unwrap()
+        ))
+        .insert_header(caller.bearer())
+}
+
+#[actix_web::test]
+async fn join_group_chat_with_invite() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+
+    let invite = app
+        .create_invite(&alice, group_chat_id, json!({ "max_uses": 2 }))
+        .await;
+    assert_eq!(invite["group_chat_id"], group_chat_id);
+    assert_eq!(invite["created_by"], alice.profile_id);
+    assert_eq!(invite["code"].as_str().unwrap().len(), 16);
+    assert_eq!(invite["uses"], 0);
+    assert_eq!(invite["expires_at"], Value::Null);
+
+    let (status, member) = app.call_json(join(&bob, &invite)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        member,
+        json!({ "profile_id": bob.profile_id, "role": "member" })
+    );
+
+    let response = app.call(join(&bob, &invite)).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let response = app.call(join(&carol, &invite)).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app.call(join(&dave, &invite)).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let (_, invites) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/invites", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(invites[0]["uses"], 2);
+
+    let (_, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(members.as_array().unwrap().len(), 3);
+
+    // revoked and expired invites can't be used anymore
+    let invite = app.create_invite(&alice, group_chat_id, json!({})).await;
+
+    let (status, revoked_invite) = app
+        .call_json(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/invites/{}",
+                    group_chat_id, invite["invite_id"]
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(revoked_invite["revoked_at"], Value::Null);
+
+    let response = app.call(join(&dave, &invite)).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let invite = app
+        .create_invite(
+            &alice,
+            group_chat_id,
+            json!({ "expires_at": "2099-01-01T00:00:00" }),
+        )
+        .await;
+
+    group_chat_invite::Entity::update_many()
+        .col_expr(
+            group_chat_invite::Column::ExpiresAt,
+            Expr::value(DateTime::default()),
+        )
+        .filter(group_chat_invite::Column::InviteId.eq(invite["invite_id"].as_i64().unwrap()))
+        .exec(&app.db_connection)
+        .await
+        .unwrap();
+
+    let response = app.call(join(&dave, &invite)).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/invite/unknown/join")
+                .insert_header(dave.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_invite_not_found",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn invites_are_managed_by_admins() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+
+    for limits in [
+        json!({ "max_uses": 0 }),
+        json!({ "expires_at": "2020-01-01T00:00:00" }),
+    ] {
+        let response = app
+            .call(
+                TestRequest::post()
+                    .uri(&format!("/group_chat/{}/invites/new", group_chat_id))
+                    .insert_header(alice.bearer())
+                    .set_json(limits),
+            )
+            .await;
+        assert_problem(
+            response,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "validation_failed",
+        )
+        .await;
+    }
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!("/group_chat/{}/invites/new", group_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({})),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/invites", group_chat_id))
+                .insert_header(carol.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "not_group_chat_member").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!(
+                    "/group_chat/{}/members/{}",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "role": "admin" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let invite = app.create_invite(&bob, group_chat_id, json!({})).await;
+    assert_eq!(invite["created_by"], bob.profile_id);
+
+    // invites of other group chats can't be revoked
+    let other_group_chat_id = app.create_group_chat(&bob, &[]).await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/invites/{}",
+                    other_group_chat_id, invite["invite_id"]
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_invite_not_found",
+    )
+    .await;
+
+    // deleting the group chat removes its invites
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/group_chat/delete/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(join(&carol, &invite)).await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_invite_not_found",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn join_requests_require_approval() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let carol = app.register("carol").await;
+    let dave = app.register("dave").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+    let invite = app.create_invite(&alice, group_chat_id, json!({})).await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "approval_required": true })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(group_chat["approval_required"], true);
+
+    for profile in [&bob, &carol, &dave] {
+        let (status, join_request) = app.call_json(join(profile, &invite)).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(join_request["group_chat_id"], group_chat_id);
+        assert_eq!(join_request["profile_id"], profile.profile_id);
+        assert_eq!(join_request["invite_id"], invite["invite_id"]);
+    }
+
+    let response = app.call(join(&bob, &invite)).await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    // pending requests don't grant access to the group chat
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "not_group_chat_member").await;
+
+    let (status, join_requests) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/join_requests", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(join_requests.as_array().unwrap().len(), 3);
+
+    let (status, member) = app
+        .call_json(
+            TestRequest::post()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}/accept",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        member,
+        json!({ "profile_id": bob.profile_id, "role": "member" })
+    );
+
+    // members can't review requests, but everybody may withdraw their own
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}",
+                    group_chat_id, carol.profile_id
+                ))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "group_chat_role_required").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}",
+                    group_chat_id, carol.profile_id
+                ))
+                .insert_header(carol.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}",
+                    group_chat_id, dave.profile_id
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}/accept",
+                    group_chat_id, dave.profile_id
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::NOT_FOUND,
+        "group_chat_join_request_not_found",
+    )
+    .await;
+
+    let (_, join_requests) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/join_requests", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(join_requests, json!([]));
+
+    let (_, members) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/members", group_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(
+        members,
+        json!([
+            { "profile_id": alice.profile_id, "role": "owner" },
+            { "profile_id": bob.profile_id, "role": "member" },
+        ])
+    );
+}
+
+#[actix_web::test]
+async fn site_admins_manage_invites_without_membership() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let admin = app.register_admin("admin").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+
+    let invite = app.create_invite(&admin, group_chat_id, json!({})).await;
+    assert_eq!(invite["created_by"], admin.profile_id);
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "approval_required": true })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, _) = app.call_json(join(&bob, &invite)).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!(
+                    "/group_chat/{}/join_requests/{}",
+                    group_chat_id, bob.profile_id
+                ))
+                .insert_header(admin.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use common::*;
+use migration::{Migrator, MigratorTrait};
+
+#[actix_web::test]
+async fn live_and_ready() {
+    let app = spawn_app().await;
+
+    let (status, liveness) = app.call_json(TestRequest::get().uri("/health/live")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(liveness["status"], "ok");
+
+    let (status, readiness) = app.call_json(TestRequest::get().uri("/health/ready")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(readiness["status"], "ready");
+    assert_eq!(
+        readiness["applied_migrations"],
+        Migrator::migrations().len()
+    );
+}
+
+#[actix_web::test]
+async fn not_ready_with_pending_migrations() {
+    let app = spawn_app().await;
+    Migrator::down(&app.db_connection, Some(1)).await.unwrap();
+
+    let response = app.call(TestRequest::get().uri("/health/ready")).await;
+    assert_problem(
+        response,
+        StatusCode::SERVICE_UNAVAILABLE,
+        "migrations_pending",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn not_ready_without_database() {
+    let app = spawn_app().await;
+    app.db_connection.clone().close().await.unwrap();
+
+    let response = app.call(TestRequest::get().uri("/health/ready")).await;
+    assert_problem(
+        response,
+        StatusCode::SERVICE_UNAVAILABLE,
+        "database_unavailable",
+    )
+    .await;
+
+    // the process itself is still alive
+    let response = app.call(TestRequest::get().uri("/health/live")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn version() {
+    let app = spawn_app().await;
+
+    let (status, version) = app.call_json(TestRequest::get().uri("/version")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+
+    let migrations: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|migration| migration.name().to_owned())
+        .collect();
+    assert_eq!(version["applied_migrations"], serde_json::json!(migrations));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use api::logger::json_layer;
+use api::request_id::REQUEST_ID_HEADER;
+use common::*;
+use serde_json::{json, Value};
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+/// Collects the log lines of a test.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl LogBuffer {
+    fn lines(&self) -> Vec<Value> {
+        let buffer = self.0.lock().unwrap();
+
+        std::str::from_utf8(&buffer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+impl io::Write for LogBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogBuffer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[actix_web::test]
+async fn request_ids_are_returned() {
+    let app = spawn_app().await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/health/live")
+                .insert_header((REQUEST_ID_HEADER, "client-id-1")),
+        )
+        .await;
+    assert_eq!(
+        response.headers().get(REQUEST_ID_HEADER).unwrap(),
+        "client-id-1"
+    );
+
+    // invalid IDs are replaced, so that they can't break the log format
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/health/live")
+                .insert_header((REQUEST_ID_HEADER, "\"; injected")),
+        )
+        .await;
+    let request_id = response.headers().get(REQUEST_ID_HEADER).unwrap();
+    assert_eq!(request_id.len(), 36);
+
+    let response = app.call(TestRequest::get().uri("/unknown")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+}
+
+#[actix_web::test]
+async fn log_lines_are_structured() {
+    let _ = tracing_log::LogTracer::init();
+    let buffer = LogBuffer::default();
+    let _guard =
+        tracing::subscriber::set_default(Registry::default().with(json_layer(buffer.clone())));
+
+    let app = spawn_app().await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/profile/new")
+                .insert_header((REQUEST_ID_HEADER, "create-alice"))
+                .set_json(json!({
+                    "username": "alice",
+                    "displayname": null,
+                    "password": PASSWORD,
+                    "email_address": "alice@example.org",
+                })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let lines = buffer.lines();
+    let line = lines
+        .iter()
+        .find(|line| line["target"] == "database::profile_operations")
+        .expect("the profile creation is logged");
+
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["operation"], "create");
+    assert_eq!(line["message"], "New profile has been created: 1");
+    assert_eq!(line["request_id"], "create-alice");
+    assert_eq!(line["method"], "POST");
+    assert_eq!(line["path"], "/profile/new");
+    assert!(line["timestamp"].is_string());
+}
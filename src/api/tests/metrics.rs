@@ -0,0 +1,49 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use common::*;
+
+#[actix_web::test]
+async fn metrics_are_exposed() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "hello group")
+        .await;
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+    app.call(TestRequest::get().uri("/unknown")).await;
+
+    let response = app.call(TestRequest::get().uri("/metrics")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let body = test::read_body(response).await;
+    let metrics = std::str::from_utf8(&body).unwrap();
+
+    for line in [
+        "sorume_profiles_created_total 2",
+        "sorume_group_chats_created_total 1",
+        "sorume_messages_sent_total{chat_kind=\"group_chat\"} 1",
+        "sorume_messages_sent_total{chat_kind=\"private_chat\"} 1",
+        "sorume_active_sessions 2",
+        "sorume_gateway_connections 0",
+        "sorume_http_requests_total{method=\"POST\",route=\"/profile/new\",status=\"201\"} 2",
+        "sorume_http_requests_total{method=\"GET\",route=\"unmatched\",status=\"404\"} 1",
+    ] {
+        assert!(metrics.lines().any(|metric| metric == line), "{}", line);
+    }
+
+    assert!(metrics.contains("sorume_http_request_duration_seconds_bucket{method=\"POST\",route=\"/group_chat/{group_chat_id}/messages/new\""));
+    assert!(metrics
+        .contains("sorume_db_query_duration_seconds_count{outcome=\"ok\",statement=\"insert\"}"));
+}
@@ -0,0 +1,466 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use common::*;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn send_and_read_private_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+    app.send_private_message(&bob, alice.profile_id, "hello alice")
+        .await;
+
+    let (status, private_chats) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/private_chats", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(private_chats.as_array().unwrap().len(), 1);
+    let private_chat_id = private_chats[0]["private_chat_id"].as_i64().unwrap();
+
+    let (status, private_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/private_chat/{}", private_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(private_chat["first_profile_id"], alice.profile_id);
+    assert_eq!(private_chat["second_profile_id"], bob.profile_id);
+
+    let (status, history) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/private_chat/{}/messages", private_chat_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["messages"][0]["content"], "hello bob");
+    assert_eq!(history["messages"][1]["content"], "hello alice");
+    assert_eq!(history["next_cursor"], Value::Null);
+
+    let (status, page) = app
+        .call_json(
+            TestRequest::post()
+                .uri("/private_message/chat?limit=1")
+                .insert_header(alice.bearer())
+                .set_json(json!({
+                    "sender_id": alice.profile_id,
+                    "recipient_id": bob.profile_id,
+                })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["content"], "hello alice");
+    assert!(page["next_cursor"].is_string());
+
+    // nothing has been sent after the latest message, not even the message itself
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let (status, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/private_chat/{}/messages?after={}",
+                    private_chat_id, cursor
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["messages"], json!([]));
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/private_chat/{}/messages?before={}",
+                    private_chat_id, cursor
+                ))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(page["messages"][0]["content"], "hello bob");
+
+    let message_id = history["messages"][0]["private_message_id"]
+        .as_i64()
+        .unwrap();
+
+    let (status, message) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/private_message/{}", message_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(message["sender_id"], alice.profile_id);
+    assert_eq!(message["recipient_id"], bob.profile_id);
+
+    let (status, read_state) = app
+        .call_json(
+            TestRequest::post()
+                .uri(&format!("/private_chat/{}/read", private_chat_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "message_id": message_id })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(read_state["profile_id"], bob.profile_id);
+
+    // the read cursor of bob already points to his own, later message
+    let latest_message_id = history["messages"][1]["private_message_id"]
+        .as_i64()
+        .unwrap();
+    assert_eq!(read_state["last_read_message_id"], latest_message_id);
+}
+
+#[actix_web::test]
+async fn private_chats_are_limited_to_participants() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let eve = app.register("eve").await;
+
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_chat/1")
+                .insert_header(eve.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_chat/1/messages")
+                .insert_header(eve.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/private_chat/1/read")
+                .insert_header(eve.bearer())
+                .set_json(json!({ "message_id": null })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_message/1")
+                .insert_header(eve.bearer()),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/private_message/chat")
+                .insert_header(eve.bearer())
+                .set_json(json!({
+                    "sender_id": alice.profile_id,
+                    "recipient_id": bob.profile_id,
+                })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/private_chats", alice.profile_id))
+                .insert_header(eve.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "profile_access_denied").await;
+}
+
+#[actix_web::test]
+async fn missing_private_chats_and_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_chat/999")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "private_chat_not_found").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_message/999")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "private_message_not_found").await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/private_message/new")
+                .insert_header(alice.bearer())
+                .set_json(json!({ "recipient_id": 999, "content": "hello?" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+}
+
+#[actix_web::test]
+async fn invalid_cursor_is_rejected() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_chat/1/messages?before=invalid")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::BAD_REQUEST, "invalid_cursor").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/private_chat/1/messages?limit=many")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::BAD_REQUEST, "invalid_query").await;
+}
+
+#[actix_web::test]
+async fn update_and_delete_private_message() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri("/private_message/1")
+                .insert_header(bob.bearer())
+                .set_json(json!({ "content": "hijacked" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri("/private_message/1")
+                .insert_header(alice.bearer())
+                .set_json(json!({ "content": "hello again" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, message) = app
+        .call_json(
+            TestRequest::get()
+                .uri("/private_message/1")
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(message["content"], "hello again");
+    assert!(message["edited_at"].is_string());
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/private_message/delete/1")
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "message_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/private_message/delete/1")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, message) = app
+        .call_json(
+            TestRequest::get()
+                .uri("/private_message/1")
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(message["content"], Value::Null);
+    assert!(message["deleted_at"].is_string());
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri("/private_message/1")
+                .insert_header(alice.bearer())
+                .set_json(json!({ "content": "undeleted" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::CONFLICT, "conflict").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/private_message/delete/999")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "private_message_not_found").await;
+}
+
+#[actix_web::test]
+async fn delete_private_chat_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let eve = app.register("eve").await;
+
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+    app.send_private_message(&bob, alice.profile_id, "hello alice")
+        .await;
+
+    let participants = json!({
+        "sender_id": alice.profile_id,
+        "recipient_id": bob.profile_id,
+    });
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/private_message/chat/delete")
+                .insert_header(eve.bearer())
+                .set_json(&participants),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::FORBIDDEN,
+        "not_private_chat_participant",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/private_message/chat/delete")
+                .insert_header(bob.bearer())
+                .set_json(&participants),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::post()
+                .uri("/private_message/chat")
+                .insert_header(alice.bearer())
+                .set_json(&participants),
+        )
+        .await;
+    assert_eq!(page["messages"], json!([]));
+}
+
+#[actix_web::test]
+async fn inbox_lists_chats_by_activity() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    app.send_private_message(&bob, alice.profile_id, "hello alice")
+        .await;
+    let group_chat_id = app.create_group_chat(&bob, &[&alice]).await;
+    app.send_group_message(&bob, group_chat_id, "hello group")
+        .await;
+
+    let (status, inbox) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/inbox", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(inbox.as_array().unwrap().len(), 2);
+
+    assert_eq!(inbox[0]["kind"], "group_chat");
+    assert_eq!(inbox[0]["chat_id"], group_chat_id);
+    assert_eq!(inbox[0]["last_message"]["preview"], "hello group");
+    assert_eq!(inbox[0]["unread_count"], 1);
+
+    assert_eq!(inbox[1]["kind"], "private_chat");
+    assert_eq!(inbox[1]["other_profile_id"], bob.profile_id);
+    assert_eq!(inbox[1]["unread_count"], 1);
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/inbox", alice.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "profile_access_denied").await;
+}
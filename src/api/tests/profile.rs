@@ -0,0 +1,586 @@
+mod common;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use common::*;
+use database::sea_orm::*;
+use database::{get_profile_by_id, ErasurePolicy};
+use entities::profile;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn login_and_logout() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/auth/logout")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the revoked token is rejected afterwards
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+    assert_problem(response, StatusCode::UNAUTHORIZED, "unauthorized").await;
+}
+
+#[actix_web::test]
+async fn login_rejects_invalid_credentials() {
+    let app = spawn_app().await;
+    app.register("alice").await;
+
+    for (username, password) in [("alice", "wrong password"), ("nobody", PASSWORD)] {
+        let response = app
+            .call(TestRequest::post().uri("/auth/login").set_json(json!({
+                "username": username,
+                "password": password,
+            })))
+            .await;
+        assert_problem(response, StatusCode::UNAUTHORIZED, "invalid_credentials").await;
+    }
+}
+
+#[actix_web::test]
+async fn login_reports_database_failures() {
+    let app = spawn_app().await;
+
+    app.db_connection
+        .execute_unprepared("DROP TABLE profile")
+        .await
+        .unwrap();
+
+    let response = app
+        .call(TestRequest::post().uri("/auth/login").set_json(json!({
+            "username": "alice",
+            "password": PASSWORD,
+        })))
+        .await;
+    assert_problem(
+        response,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "internal_error",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn plaintext_passwords_are_hashed_on_login() {
+    let app = spawn_app().await;
+
+    // profiles created before hashing store their password in plaintext
+    let legacy_profile = profile::ActiveModel {
+        username: Set("legacy".to_owned()),
+        password: Set(PASSWORD.to_owned()),
+        email_address: Set("legacy@example.com".to_owned()),
+        join_datetime: Set(Default::default()),
+        is_admin: Set(false),
+        ..Default::default()
+    }
+    .insert(&app.db_connection)
+    .await
+    .unwrap();
+
+    let response = app
+        .call(TestRequest::post().uri("/auth/login").set_json(json!({
+            "username": "legacy",
+            "password": "wrong password",
+        })))
+        .await;
+    assert_problem(response, StatusCode::UNAUTHORIZED, "invalid_credentials").await;
+
+    app.login("legacy").await;
+
+    let stored_profile = get_profile_by_id(legacy_profile.profile_id, &app.db_connection)
+        .await
+        .unwrap();
+    assert!(stored_profile.password.starts_with("$argon2id$"));
+
+    // the upgraded hash keeps working
+    app.login("legacy").await;
+}
+
+#[actix_web::test]
+async fn token_lookup_failures_are_server_errors() {
+    let app = spawn_app().await;
+    app.register("alice").await;
+    let token = app.login("alice").await;
+
+    app.db_connection
+        .execute_unprepared("DROP TABLE session")
+        .await
+        .unwrap();
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/auth/logout")
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token))),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "internal_error",
+    )
+    .await;
+}
+
+#[actix_web::test]
+async fn corrupted_password_hashes_are_not_compared_as_plaintext() {
+    let app = spawn_app().await;
+
+    let corrupted_hash = "$argon2id$v=19$corrupted";
+    profile::ActiveModel {
+        username: Set("corrupted".to_owned()),
+        password: Set(corrupted_hash.to_owned()),
+        email_address: Set("corrupted@example.com".to_owned()),
+        join_datetime: Set(Default::default()),
+        is_admin: Set(false),
+        ..Default::default()
+    }
+    .insert(&app.db_connection)
+    .await
+    .unwrap();
+
+    let response = app
+        .call(TestRequest::post().uri("/auth/login").set_json(json!({
+            "username": "corrupted",
+            "password": corrupted_hash,
+        })))
+        .await;
+    assert_problem(response, StatusCode::UNAUTHORIZED, "invalid_credentials").await;
+}
+
+#[actix_web::test]
+async fn requests_without_token_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.call(TestRequest::post().uri("/auth/logout")).await;
+    assert_problem(response, StatusCode::UNAUTHORIZED, "unauthorized").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/profile/1")
+                .insert_header((header::AUTHORIZATION, "Bearer unknown")),
+        )
+        .await;
+    assert_problem(response, StatusCode::UNAUTHORIZED, "unauthorized").await;
+}
+
+#[actix_web::test]
+async fn new_profile_rejects_invalid_username() {
+    let app = spawn_app().await;
+
+    for username in ["with whitespace", &"a".repeat(33)] {
+        let response = app
+            .call(TestRequest::post().uri("/profile/new").set_json(json!({
+                "username": username,
+                "displayname": null,
+                "password": PASSWORD,
+                "email_address": "invalid@example.org",
+            })))
+            .await;
+        assert_problem(
+            response,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "validation_failed",
+        )
+        .await;
+    }
+}
+
+#[actix_web::test]
+async fn new_profile_rejects_malformed_body() {
+    let app = spawn_app().await;
+
+    let response = app
+        .call(
+            TestRequest::post()
+                .uri("/profile/new")
+                .set_json(json!({ "username": "alice" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::BAD_REQUEST, "invalid_body").await;
+}
+
+#[actix_web::test]
+async fn get_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let (status, profile) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}", bob.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["username"], "bob");
+    assert_eq!(profile["email_address"], "bob@example.org");
+
+    let (status, profile) = app
+        .call_json(
+            TestRequest::get()
+                .uri("/profile/username/bob")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(profile["username"], "bob");
+}
+
+#[actix_web::test]
+async fn get_profile_of_missing_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/profile/999")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/profile/username/nobody")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri("/profile/alice")
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::BAD_REQUEST, "invalid_path").await;
+}
+
+#[actix_web::test]
+async fn update_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+
+    let (_, before) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "displayname": "Alice" })),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, after) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(after["displayname"], "Alice");
+    assert_eq!(after["username"], "alice");
+    assert_eq!(after["join_datetime"], before["join_datetime"]);
+
+    // the unchanged password keeps working
+    app.login("alice").await;
+}
+
+#[actix_web::test]
+async fn update_profile_rejects_invalid_requests() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let admin = app.register_admin("admin").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer())
+                .set_json(json!({ "username": "with whitespace" })),
+        )
+        .await;
+    assert_problem(
+        response,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+    )
+    .await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(bob.bearer())
+                .set_json(json!({ "displayname": "Bob" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "profile_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::patch()
+                .uri("/profile/999")
+                .insert_header(admin.bearer())
+                .set_json(json!({ "displayname": "Nobody" })),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+}
+
+#[actix_web::test]
+async fn delete_profile_anonymizes_messages() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "hello group")
+        .await;
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+
+    let (status, audit) = app
+        .call_json(
+            TestRequest::delete()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(audit["profile_id"], alice.profile_id);
+    assert_eq!(audit["policy"], "anonymize");
+    assert_eq!(audit["removed_memberships"], 1);
+    assert_eq!(audit["affected_group_messages"], 1);
+    assert_eq!(audit["affected_private_messages"], 1);
+    assert_eq!(audit["revoked_sessions"], 1);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"][0]["author_id"], Value::Null);
+    assert_eq!(page["messages"][0]["content"], "hello group");
+
+    let (_, inbox) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/inbox", bob.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    let group_chat_entry = inbox
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["kind"] == "group_chat")
+        .unwrap();
+    assert_eq!(group_chat_entry["chat_id"], group_chat_id);
+    assert_eq!(group_chat_entry["unread_count"], 1);
+
+    // the group chat outlives its creator
+    let (status, group_chat) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(group_chat["created_by"], Value::Null);
+    assert_eq!(
+        group_chat["members"],
+        json!([{ "profile_id": bob.profile_id, "role": "owner" }])
+    );
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}", bob.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::UNAUTHORIZED, "unauthorized").await;
+}
+
+#[actix_web::test]
+async fn delete_profile_deletes_messages() {
+    let app = spawn_app_with_policy(ErasurePolicy::Delete).await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "hello group")
+        .await;
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+    app.send_private_message(&bob, alice.profile_id, "hello alice")
+        .await;
+
+    let (status, audit) = app
+        .call_json(
+            TestRequest::delete()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(audit["policy"], "delete");
+    assert_eq!(audit["affected_group_messages"], 1);
+    // the message of alice is deleted, the one of bob is detached
+    assert_eq!(audit["affected_private_messages"], 2);
+
+    let (_, page) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}/messages", group_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(page["messages"], json!([]));
+
+    let (_, private_chats) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/private_chats", bob.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(private_chats.as_array().unwrap().len(), 1);
+    let private_chat_id = private_chats[0]["private_chat_id"].as_i64().unwrap();
+
+    let (_, history) = app
+        .call_json(
+            TestRequest::get()
+                .uri(&format!("/private_chat/{}/messages", private_chat_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_eq!(history["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(history["messages"][0]["content"], "hello alice");
+    assert_eq!(history["messages"][0]["recipient_id"], Value::Null);
+}
+
+#[actix_web::test]
+async fn delete_profile_rejects_invalid_requests() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+    let admin = app.register_admin("admin").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri(&format!("/profile/{}", alice.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "profile_access_denied").await;
+
+    let response = app
+        .call(
+            TestRequest::delete()
+                .uri("/profile/999")
+                .insert_header(admin.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::NOT_FOUND, "profile_not_found").await;
+}
+
+#[actix_web::test]
+async fn export_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let group_chat_id = app.create_group_chat(&alice, &[&bob]).await;
+    app.send_group_message(&alice, group_chat_id, "hello group")
+        .await;
+    app.send_private_message(&alice, bob.profile_id, "hello bob")
+        .await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/export", alice.profile_id))
+                .insert_header(alice.bearer()),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/x-ndjson"
+    );
+
+    let body = test::read_body(response).await;
+    let records: Vec<Value> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let types: Vec<&str> = records
+        .iter()
+        .map(|record| record["type"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(
+        types,
+        [
+            "profile",
+            "group_chat_membership",
+            "group_chat_message",
+            "private_chat",
+            "private_message"
+        ]
+    );
+    assert_eq!(records[0]["data"]["username"], "alice");
+    assert_eq!(records[0]["data"].get("password"), None);
+    assert_eq!(records[1]["data"]["name"], "Test group");
+}
+
+#[actix_web::test]
+async fn export_profile_of_other_profile() {
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let bob = app.register("bob").await;
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/profile/{}/export", alice.profile_id))
+                .insert_header(bob.bearer()),
+        )
+        .await;
+    assert_problem(response, StatusCode::FORBIDDEN, "profile_access_denied").await;
+}
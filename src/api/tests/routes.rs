@@ -0,0 +1,56 @@
+mod common;
+
+use actix_web::http::{header, Method};
+use actix_web::test::TestRequest;
+use api::openapi::ApiDoc;
+use common::*;
+use utoipa::openapi::PathItemType;
+use utoipa::OpenApi;
+
+/// Every documented route has to be registered. Requests without credentials or body are
+/// rejected with a problem document and public routes succeed, whereas unknown routes fall through
+/// to an empty 404.
+#[actix_web::test]
+async fn every_documented_route_is_registered() {
+    let app = spawn_app().await;
+    let openapi = ApiDoc::openapi();
+
+    for (path, path_item) in openapi.paths.paths {
+        let uri = path
+            .split('/')
+            .map(|segment| match segment.starts_with('{') {
+                true => "1",
+                false => segment,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        for operation in path_item.operations.keys() {
+            let method = match operation {
+                PathItemType::Get => Method::GET,
+                PathItemType::Post => Method::POST,
+                PathItemType::Put => Method::PUT,
+                PathItemType::Patch => Method::PATCH,
+                PathItemType::Delete => Method::DELETE,
+                _ => panic!("Unexpected operation of {}", path),
+            };
+
+            let response = app
+                .call(TestRequest::default().method(method.clone()).uri(&uri))
+                .await;
+
+            let is_problem = response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .is_some_and(|content_type| content_type == "application/problem+json");
+
+            assert!(
+                is_problem || response.status().is_success(),
+                "{} {} is not routed, responded with {}",
+                method,
+                path,
+                response.status()
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use api::telemetry::trace_layer;
+use common::*;
+use opentelemetry::trace::{SpanId, SpanKind, TraceId};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+fn find_span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("{} has been exported", name))
+}
+
+#[actix_web::test]
+async fn spans_continue_the_trace_of_the_client() {
+    let exporter = InMemorySpanExporter::default();
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let _guard =
+        tracing::subscriber::set_default(Registry::default().with(trace_layer(&tracer_provider)));
+
+    let app = spawn_app().await;
+    let alice = app.register("alice").await;
+    let group_chat_id = app.create_group_chat(&alice, &[]).await;
+    exporter.reset();
+
+    let response = app
+        .call(
+            TestRequest::get()
+                .uri(&format!("/group_chat/{}", group_chat_id))
+                .insert_header(alice.bearer())
+                .insert_header((
+                    "traceparent",
+                    format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID),
+                )),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let request = find_span(&spans, "GET /group_chat/{group_chat_id}");
+
+    assert_eq!(
+        request.span_context.trace_id(),
+        TraceId::from_hex(TRACE_ID).unwrap()
+    );
+    assert_eq!(
+        request.parent_span_id,
+        SpanId::from_hex(PARENT_SPAN_ID).unwrap()
+    );
+    assert_eq!(request.span_kind, SpanKind::Server);
+    assert!(request
+        .attributes
+        .contains(&KeyValue::new("http.response.status_code", 200)));
+
+    // the lookups of the handler are children of the request
+    for name in ["get_session_by_token", "get_group_chat_by_id"] {
+        let operation = find_span(&spans, name);
+
+        assert_eq!(
+            operation.span_context.trace_id(),
+            request.span_context.trace_id()
+        );
+        assert_eq!(operation.parent_span_id, request.span_context.span_id());
+    }
+    assert!(find_span(&spans, "get_group_chat_by_id")
+        .attributes
+        .contains(&KeyValue::new("group_chat_id", group_chat_id as i64)));
+
+    // without a traceparent header, every request starts a new trace
+    exporter.reset();
+    app.call(TestRequest::get().uri("/health/live")).await;
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let request = find_span(&spans, "GET /health/live");
+
+    assert_ne!(
+        request.span_context.trace_id(),
+        TraceId::from_hex(TRACE_ID).unwrap()
+    );
+    assert_eq!(request.parent_span_id, SpanId::INVALID);
+}
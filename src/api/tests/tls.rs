@@ -0,0 +1,83 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use actix_web::App;
+use api::tls::{redirect_config, CertificateResolver};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn certificates_are_reloaded() {
+    let directory = std::env::temp_dir().join(format!("sorume-tls-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let certificate_path = directory.join("cert.pem");
+    let private_key_path = directory.join("key.pem");
+
+    fs::copy(fixture("first.cert.pem"), &certificate_path).unwrap();
+    fs::copy(fixture("first.key.pem"), &private_key_path).unwrap();
+
+    let resolver = CertificateResolver::load(&certificate_path, &private_key_path).unwrap();
+    let first_certificate = resolver.current_certificate();
+
+    fs::copy(fixture("second.cert.pem"), &certificate_path).unwrap();
+    fs::copy(fixture("second.key.pem"), &private_key_path).unwrap();
+
+    resolver.reload().unwrap();
+    let second_certificate = resolver.current_certificate();
+    assert_ne!(first_certificate, second_certificate);
+
+    // a broken renewal keeps the previous certificate
+    fs::write(&private_key_path, "not a key").unwrap();
+    assert!(resolver.reload().is_err());
+    assert_eq!(resolver.current_certificate(), second_certificate);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn invalid_certificates_are_rejected() {
+    let err = CertificateResolver::load(&fixture("first.key.pem"), &fixture("first.key.pem"))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("no certificates"), "{}", err);
+
+    let err = CertificateResolver::load(&fixture("first.cert.pem"), &fixture("first.cert.pem"))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("no private key"), "{}", err);
+}
+
+#[actix_web::test]
+async fn http_requests_are_redirected() {
+    let service = test::init_service(App::new().configure(redirect_config(8443))).await;
+
+    let request = TestRequest::post()
+        .uri("/profile/new?x=1")
+        .insert_header((header::HOST, "example.org:8080"))
+        .to_request();
+    let response = test::call_service(&service, request).await;
+
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response.headers().get(header::LOCATION).unwrap(),
+        "https://example.org:8443/profile/new?x=1"
+    );
+
+    let service = test::init_service(App::new().configure(redirect_config(443))).await;
+
+    let request = TestRequest::get()
+        .uri("/")
+        .insert_header((header::HOST, "example.org"))
+        .to_request();
+    let response = test::call_service(&service, request).await;
+
+    assert_eq!(
+        response.headers().get(header::LOCATION).unwrap(),
+        "https://example.org/"
+    );
+}
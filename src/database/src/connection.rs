@@ -1,23 +1,52 @@
-use dotenvy::dotenv;
 use sea_orm::*;
+use serde::Deserialize;
 use std::env;
+use std::time::Duration;
 
-pub async fn connect_to_database(use_database: bool) -> Result<DatabaseConnection, DbErr> {
-    dotenv().ok();
-
-    let db_user = env::var("MARIADB_USER").expect("Couldn't find MARIADB_USER.");
-    let db_user_password =
-        env::var("MARIADB_USER_PASSWORD").expect("Couldn't find MARIADB_USER_PASSWORD.");
-    let db_database = env::var("MARIADB_DATABASE").expect("Couldn't find MARIADB_DATABASE.");
-    let db_host = env::var("MARIADB_HOST").expect("Couldn't find MARIADB_HOST.");
-
-    let mut connection_string = format!("mysql://{}:{}@{}", db_user, db_user_password, db_host);
+/// Connection settings of the database.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// URL of the database, e.g. `mysql://…`, `postgres://…` or `sqlite://sorume.db?mode=rwc`.
+    /// The backend has to be enabled as a cargo feature.
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout_seconds: u64,
+}
 
-    if use_database {
-        connection_string = format!("{}/{}", connection_string, db_database);
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: String::new(),
+            max_connections: 10,
+            min_connections: 1,
+            connect_timeout_seconds: 8,
+        }
     }
+}
 
-    let db_connection = Database::connect(connection_string).await?;
+pub async fn connect_to_database(config: &DatabaseConfig) -> Result<DatabaseConnection, DbErr> {
+    let mut options = ConnectOptions::new(config.url.to_owned());
+    options
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds));
+
+    let db_connection = Database::connect(options).await?;
 
     Ok(db_connection)
 }
+
+/// Builds a MariaDB URL from the `MARIADB_*` variables, if all of them are set.
+pub fn mariadb_url_from_env() -> Option<String> {
+    let db_user = env::var("MARIADB_USER").ok()?;
+    let db_user_password = env::var("MARIADB_USER_PASSWORD").ok()?;
+    let db_database = env::var("MARIADB_DATABASE").ok()?;
+    let db_host = env::var("MARIADB_HOST").ok()?;
+
+    Some(format!(
+        "mysql://{}:{}@{}/{}",
+        db_user, db_user_password, db_host, db_database
+    ))
+}
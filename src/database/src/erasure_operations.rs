@@ -0,0 +1,245 @@
+use crate::{
+    delete_join_requests_of_profile, delete_memberships_of_profile, delete_sessions_of_profile,
+    get_profile_by_id, Error,
+};
+use chrono::Local;
+use entities::*;
+use log::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::Deserialize;
+use std::str::FromStr;
+use tracing::instrument;
+
+/// Decides what happens to the messages of an erased profile.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ErasurePolicy {
+    /// Keep the messages, but detach them from the erased profile
+    #[default]
+    Anonymize,
+    /// Delete the group and private messages sent by the profile, the private chats are kept for
+    /// the other participants
+    Delete,
+}
+
+impl ErasurePolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErasurePolicy::Anonymize => "anonymize",
+            ErasurePolicy::Delete => "delete",
+        }
+    }
+}
+
+impl FromStr for ErasurePolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "anonymize" => Ok(ErasurePolicy::Anonymize),
+            "delete" => Ok(ErasurePolicy::Delete),
+            _ => Err("expected anonymize or delete".to_owned()),
+        }
+    }
+}
+
+/// Deletes a profile together with its memberships and sessions and handles its messages according
+/// to the given policy. Everything happens within a single transaction, which also records an
+/// audit entry of the erasure.
+#[instrument(skip(connection))]
+pub async fn erase_profile(
+    profile_id: i32,
+    requested_by: i32,
+    policy: ErasurePolicy,
+    connection: &DbConn,
+) -> Result<erasure_audit::Model, Error> {
+    let transaction = connection.begin().await?;
+
+    get_profile_by_id(profile_id, &transaction).await?;
+
+    let removed_memberships = delete_memberships_of_profile(profile_id, &transaction)
+        .await?
+        .rows_affected;
+    let revoked_sessions = delete_sessions_of_profile(profile_id, &transaction)
+        .await?
+        .rows_affected;
+    delete_join_requests_of_profile(profile_id, &transaction).await?;
+    clear_group_chat_creator(profile_id, &transaction).await?;
+    clear_invite_creator(profile_id, &transaction).await?;
+
+    let (affected_group_messages, affected_private_messages) = match policy {
+        ErasurePolicy::Anonymize => (
+            anonymize_group_messages(profile_id, &transaction).await?,
+            anonymize_private_chats(profile_id, &transaction).await?,
+        ),
+        ErasurePolicy::Delete => (
+            delete_group_messages(profile_id, &transaction).await?,
+            delete_private_chats(profile_id, &transaction).await?,
+        ),
+    };
+
+    profile::Entity::delete_by_id(profile_id)
+        .exec(&transaction)
+        .await?;
+
+    let audit_entry = erasure_audit::ActiveModel {
+        profile_id: ActiveValue::Set(profile_id),
+        requested_by: ActiveValue::Set(requested_by),
+        policy: ActiveValue::Set(policy.name().to_owned()),
+        erased_at: ActiveValue::Set(Local::now().naive_local()),
+        removed_memberships: ActiveValue::Set(removed_memberships as i64),
+        affected_group_messages: ActiveValue::Set(affected_group_messages as i64),
+        affected_private_messages: ActiveValue::Set(affected_private_messages as i64),
+        revoked_sessions: ActiveValue::Set(revoked_sessions as i64),
+        ..Default::default()
+    }
+    .insert(&transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    info!(
+        "D: Profile {:?} has been erased using the {} policy",
+        profile_id,
+        policy.name()
+    );
+
+    Ok(audit_entry)
+}
+
+/// Group chats outlive their creator, only the reference to the erased profile is removed.
+#[instrument(skip(connection))]
+async fn clear_group_chat_creator<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    Ok(group_chat::Entity::update_many()
+        .col_expr(
+            group_chat::Column::CreatedBy,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(group_chat::Column::CreatedBy.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected)
+}
+
+/// Invites stay usable, only the reference to the erased profile is removed.
+#[instrument(skip(connection))]
+async fn clear_invite_creator<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    Ok(group_chat_invite::Entity::update_many()
+        .col_expr(
+            group_chat_invite::Column::CreatedBy,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(group_chat_invite::Column::CreatedBy.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected)
+}
+
+#[instrument(skip(connection))]
+async fn anonymize_group_messages<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    Ok(group_chat_message::Entity::update_many()
+        .col_expr(
+            group_chat_message::Column::AuthorId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(group_chat_message::Column::AuthorId.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected)
+}
+
+#[instrument(skip(connection))]
+async fn delete_group_messages<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    Ok(group_chat_message::Entity::delete_many()
+        .filter(group_chat_message::Column::AuthorId.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected)
+}
+
+/// Detaches the profile from its private messages and chats, so that the other participants keep
+/// their history.
+#[instrument(skip(connection))]
+async fn anonymize_private_chats<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    let sent_messages = private_message::Entity::update_many()
+        .col_expr(
+            private_message::Column::SenderId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(private_message::Column::SenderId.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected;
+
+    let received_messages = private_message::Entity::update_many()
+        .col_expr(
+            private_message::Column::RecipientId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(private_message::Column::RecipientId.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected;
+
+    private_chat::Entity::update_many()
+        .col_expr(
+            private_chat::Column::FirstProfileId,
+            Expr::value(Option::<i32>::None),
+        )
+        .col_expr(
+            private_chat::Column::FirstLastReadMessageId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(private_chat::Column::FirstProfileId.eq(profile_id))
+        .exec(connection)
+        .await?;
+
+    private_chat::Entity::update_many()
+        .col_expr(
+            private_chat::Column::SecondProfileId,
+            Expr::value(Option::<i32>::None),
+        )
+        .col_expr(
+            private_chat::Column::SecondLastReadMessageId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(private_chat::Column::SecondProfileId.eq(profile_id))
+        .exec(connection)
+        .await?;
+
+    Ok(sent_messages + received_messages)
+}
+
+/// Deletes the private messages sent by the profile, the messages of the other participants and
+/// the chats are detached as in [`anonymize_private_chats`].
+#[instrument(skip(connection))]
+async fn delete_private_chats<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<u64, Error> {
+    let deleted_messages = private_message::Entity::delete_many()
+        .filter(private_message::Column::SenderId.eq(profile_id))
+        .exec(connection)
+        .await?
+        .rows_affected;
+
+    let detached_messages = anonymize_private_chats(profile_id, connection).await?;
+
+    Ok(deleted_messages + detached_messages)
+}
@@ -0,0 +1,112 @@
+use sea_orm::{DbErr, SqlErr};
+use std::fmt;
+
+/// Kind of a stored entity, used to describe which lookup failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    Profile,
+    Session,
+    GroupChat,
+    GroupChatInvite,
+    GroupChatJoinRequest,
+    GroupChatMember,
+    GroupChatMessage,
+    PrivateChat,
+    PrivateMessage,
+}
+
+impl EntityKind {
+    /// Stable snake case name, e.g. for error codes
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntityKind::Profile => "profile",
+            EntityKind::Session => "session",
+            EntityKind::GroupChat => "group_chat",
+            EntityKind::GroupChatInvite => "group_chat_invite",
+            EntityKind::GroupChatJoinRequest => "group_chat_join_request",
+            EntityKind::GroupChatMember => "group_chat_member",
+            EntityKind::GroupChatMessage => "group_chat_message",
+            EntityKind::PrivateChat => "private_chat",
+            EntityKind::PrivateMessage => "private_message",
+        }
+    }
+
+    /// Human readable name
+    pub fn description(&self) -> &'static str {
+        match self {
+            EntityKind::Profile => "profile",
+            EntityKind::Session => "session",
+            EntityKind::GroupChat => "group chat",
+            EntityKind::GroupChatInvite => "group chat invite",
+            EntityKind::GroupChatJoinRequest => "group chat join request",
+            EntityKind::GroupChatMember => "group chat member",
+            EntityKind::GroupChatMessage => "group chat message",
+            EntityKind::PrivateChat => "private chat",
+            EntityKind::PrivateMessage => "private message",
+        }
+    }
+}
+
+/// Failure of a database operation.
+#[derive(Debug)]
+pub enum Error {
+    /// The entity with the given identifier (or username) doesn't exist
+    NotFound { entity: EntityKind, id: String },
+    /// The input has been rejected before it reached the database
+    Validation(String),
+    /// The operation contradicts the current state, e.g. a duplicate username
+    Conflict(String),
+    /// The caller isn't allowed to perform the operation
+    Forbidden(String),
+    /// Unexpected error of the database itself
+    Db(DbErr),
+}
+
+impl Error {
+    pub fn not_found(entity: EntityKind, id: impl ToString) -> Self {
+        Error::NotFound {
+            entity,
+            id: id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound { entity, id } => write!(
+                f,
+                "Couldn't find a {} with the identifier {}.",
+                entity.description(),
+                id
+            ),
+            Error::Validation(message) | Error::Conflict(message) | Error::Forbidden(message) => {
+                write!(f, "{}", message)
+            }
+            Error::Db(err) => write!(f, "Database error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Db(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<DbErr> for Error {
+    fn from(err: DbErr) -> Self {
+        match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => {
+                Error::Conflict("The entity already exists.".to_owned())
+            }
+            Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                Error::Conflict("The operation conflicts with a related entity.".to_owned())
+            }
+            _ => Error::Db(err),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    check_group_chat_exists, get_group_chat_by_id, get_group_chat_membership,
+    insert_group_chat_join_request, insert_group_chat_member, EntityKind, Error, GroupChatRole,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Local, NaiveDateTime};
+use entities::*;
+use log::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use tracing::instrument;
+
+/// Number of random bytes of an invite code, which are encoded as 16 URL-safe characters.
+const INVITE_CODE_BYTES: usize = 12;
+
+/// Limits of a new invite, an invite without limits stays usable until it is revoked.
+#[derive(Debug, Default)]
+pub struct InviteLimits {
+    pub expires_at: Option<NaiveDateTime>,
+    pub max_uses: Option<i32>,
+}
+
+/// Result of [`join_group_chat_with_invite`].
+pub enum JoinOutcome {
+    /// The profile has become a member of the group chat
+    Joined(group_chat_member::Model),
+    /// The group chat requires approval, the request waits for its admins
+    Requested(group_chat_join_request::Model),
+}
+
+/// Creates an invite with a random code for a group chat.
+#[instrument(skip(connection))]
+pub async fn insert_group_chat_invite(
+    group_chat_id: i32,
+    created_by: i32,
+    limits: InviteLimits,
+    connection: &DbConn,
+) -> Result<group_chat_invite::Model, Error> {
+    let created_at = Local::now().naive_local();
+
+    if limits.max_uses.is_some_and(|max_uses| max_uses < 1) {
+        return Err(Error::Validation(
+            "Invites must allow at least one use.".to_owned(),
+        ));
+    }
+
+    if limits
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= created_at)
+    {
+        return Err(Error::Validation(
+            "Invites must expire in the future.".to_owned(),
+        ));
+    }
+
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
+
+    let mut code_bytes = [0u8; INVITE_CODE_BYTES];
+    OsRng.fill_bytes(&mut code_bytes);
+
+    let new_invite = group_chat_invite::ActiveModel {
+        group_chat_id: ActiveValue::Set(target_group_chat.group_chat_id),
+        code: ActiveValue::Set(URL_SAFE_NO_PAD.encode(code_bytes)),
+        created_by: ActiveValue::Set(Some(created_by)),
+        created_at: ActiveValue::Set(created_at),
+        expires_at: ActiveValue::Set(limits.expires_at),
+        max_uses: ActiveValue::Set(limits.max_uses),
+        uses: ActiveValue::Set(0),
+        ..Default::default()
+    }
+    .insert(connection)
+    .await?;
+
+    info!(
+        "C: New invite has been created for group chat: {:?}",
+        new_invite.group_chat_id
+    );
+
+    Ok(new_invite)
+}
+
+#[instrument(skip(connection))]
+pub async fn get_invites_of_group(
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> Result<Vec<group_chat_invite::Model>, Error> {
+    check_group_chat_exists(group_chat_id, connection).await?;
+
+    Ok(group_chat_invite::Entity::find()
+        .filter(group_chat_invite::Column::GroupChatId.eq(group_chat_id))
+        .order_by_asc(group_chat_invite::Column::InviteId)
+        .all(connection)
+        .await?)
+}
+
+#[instrument(skip(code, connection))]
+pub async fn get_group_chat_invite_by_code<C: ConnectionTrait>(
+    code: &str,
+    connection: &C,
+) -> Result<group_chat_invite::Model, Error> {
+    group_chat_invite::Entity::find()
+        .filter(group_chat_invite::Column::Code.eq(code))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::GroupChatInvite, code))
+}
+
+/// Revokes an invite of a group chat, revoking it again keeps the original time.
+#[instrument(skip(connection))]
+pub async fn revoke_group_chat_invite(
+    group_chat_id: i32,
+    invite_id: i32,
+    connection: &DbConn,
+) -> Result<group_chat_invite::Model, Error> {
+    let target_invite = group_chat_invite::Entity::find_by_id(invite_id)
+        .filter(group_chat_invite::Column::GroupChatId.eq(group_chat_id))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::GroupChatInvite, invite_id))?;
+
+    if target_invite.revoked_at.is_some() {
+        return Ok(target_invite);
+    }
+
+    let mut target_invite: group_chat_invite::ActiveModel = target_invite.into();
+    target_invite.revoked_at = Set(Some(Local::now().naive_local()));
+    let target_invite = target_invite.update(connection).await?;
+
+    info!(
+        "U: Invite {:?} of group chat {:?} has been revoked",
+        target_invite.invite_id, target_invite.group_chat_id
+    );
+
+    Ok(target_invite)
+}
+
+/// Joins the group chat of an invite, or requests to join it if the group chat requires approval.
+///
+/// Both count as a use of the invite. Revoked, expired and used up invites are rejected, just like
+/// profiles which are already members or have already requested to join.
+#[instrument(skip(code, connection))]
+pub async fn join_group_chat_with_invite(
+    code: &str,
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<JoinOutcome, Error> {
+    let transaction = connection.begin().await?;
+
+    let target_invite = get_group_chat_invite_by_code(code, &transaction).await?;
+
+    if target_invite.revoked_at.is_some() {
+        return Err(Error::Conflict("This invite has been revoked.".to_owned()));
+    }
+
+    if target_invite
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Local::now().naive_local())
+    {
+        return Err(Error::Conflict("This invite has expired.".to_owned()));
+    }
+
+    match get_group_chat_membership(profile_id, target_invite.group_chat_id, &transaction).await {
+        Ok(_) => {
+            return Err(Error::Conflict(
+                "You are already a member of this group chat.".to_owned(),
+            ))
+        }
+        Err(Error::NotFound { .. }) => {}
+        Err(err) => return Err(err),
+    }
+
+    // the limit is checked by the update itself, so that concurrent joins can't exceed it
+    let counted_uses = group_chat_invite::Entity::update_many()
+        .col_expr(
+            group_chat_invite::Column::Uses,
+            Expr::col(group_chat_invite::Column::Uses).add(1),
+        )
+        .filter(group_chat_invite::Column::InviteId.eq(target_invite.invite_id))
+        .filter(
+            Condition::any()
+                .add(group_chat_invite::Column::MaxUses.is_null())
+                .add(
+                    Expr::col(group_chat_invite::Column::Uses)
+                        .lt(Expr::col(group_chat_invite::Column::MaxUses)),
+                ),
+        )
+        .exec(&transaction)
+        .await?
+        .rows_affected;
+
+    if counted_uses == 0 {
+        return Err(Error::Conflict("This invite has been used up.".to_owned()));
+    }
+
+    let target_group_chat = get_group_chat_by_id(target_invite.group_chat_id, &transaction).await?;
+
+    let outcome = if target_group_chat.approval_required {
+        JoinOutcome::Requested(
+            insert_group_chat_join_request(
+                target_group_chat.group_chat_id,
+                profile_id,
+                Some(target_invite.invite_id),
+                &transaction,
+            )
+            .await?,
+        )
+    } else {
+        JoinOutcome::Joined(
+            insert_group_chat_member(
+                profile_id,
+                target_group_chat.group_chat_id,
+                GroupChatRole::Member,
+                &transaction,
+            )
+            .await?,
+        )
+    };
+
+    transaction.commit().await?;
+
+    info!(
+        "U: Profile {:?} has used invite {:?} of group chat {:?}",
+        profile_id, target_invite.invite_id, target_invite.group_chat_id
+    );
+
+    Ok(outcome)
+}
+
+/// Deletes the invites of a group chat, pending join requests have to be deleted beforehand.
+#[instrument(skip(connection))]
+pub async fn delete_invites_of_group<C: ConnectionTrait>(
+    group_chat_id: i32,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    Ok(group_chat_invite::Entity::delete_many()
+        .filter(group_chat_invite::Column::GroupChatId.eq(group_chat_id))
+        .exec(connection)
+        .await?)
+}
@@ -0,0 +1,147 @@
+use crate::{
+    check_group_chat_exists, check_profile_exists, insert_group_chat_member, EntityKind, Error,
+    GroupChatRole,
+};
+use chrono::Local;
+use entities::*;
+use log::*;
+use sea_orm::*;
+use tracing::instrument;
+
+/// Records a request of a profile to join a group chat, a profile may only have one pending
+/// request per group chat.
+#[instrument(skip(connection))]
+pub async fn insert_group_chat_join_request<C: ConnectionTrait>(
+    group_chat_id: i32,
+    profile_id: i32,
+    invite_id: Option<i32>,
+    connection: &C,
+) -> Result<group_chat_join_request::Model, Error> {
+    let target_profile = check_profile_exists(profile_id, connection).await?;
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
+
+    match get_group_chat_join_request(group_chat_id, profile_id, connection).await {
+        Ok(_) => {
+            return Err(Error::Conflict(
+                "You have already requested to join this group chat.".to_owned(),
+            ))
+        }
+        Err(Error::NotFound { .. }) => {}
+        Err(err) => return Err(err),
+    }
+
+    let new_join_request = group_chat_join_request::ActiveModel {
+        group_chat_id: ActiveValue::Set(target_group_chat.group_chat_id),
+        profile_id: ActiveValue::Set(target_profile.profile_id),
+        invite_id: ActiveValue::Set(invite_id),
+        created_at: ActiveValue::Set(Local::now().naive_local()),
+        ..Default::default()
+    }
+    .insert(connection)
+    .await?;
+
+    info!(
+        "C: Profile {:?} has requested to join group chat {:?}",
+        new_join_request.profile_id, new_join_request.group_chat_id
+    );
+
+    Ok(new_join_request)
+}
+
+#[instrument(skip(connection))]
+pub async fn get_join_requests_of_group(
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> Result<Vec<group_chat_join_request::Model>, Error> {
+    check_group_chat_exists(group_chat_id, connection).await?;
+
+    Ok(group_chat_join_request::Entity::find()
+        .filter(group_chat_join_request::Column::GroupChatId.eq(group_chat_id))
+        .order_by_asc(group_chat_join_request::Column::JoinRequestId)
+        .all(connection)
+        .await?)
+}
+
+#[instrument(skip(connection))]
+pub async fn get_group_chat_join_request<C: ConnectionTrait>(
+    group_chat_id: i32,
+    profile_id: i32,
+    connection: &C,
+) -> Result<group_chat_join_request::Model, Error> {
+    group_chat_join_request::Entity::find()
+        .filter(group_chat_join_request::Column::GroupChatId.eq(group_chat_id))
+        .filter(group_chat_join_request::Column::ProfileId.eq(profile_id))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(
+            EntityKind::GroupChatJoinRequest,
+            profile_id,
+        ))
+}
+
+/// Accepts the pending request of a profile, which joins the group chat as a member.
+#[instrument(skip(connection))]
+pub async fn accept_group_chat_join_request(
+    group_chat_id: i32,
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<group_chat_member::Model, Error> {
+    let transaction = connection.begin().await?;
+
+    get_group_chat_join_request(group_chat_id, profile_id, &transaction).await?;
+
+    // adding the member removes the request
+    let new_member = insert_group_chat_member(
+        profile_id,
+        group_chat_id,
+        GroupChatRole::Member,
+        &transaction,
+    )
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(new_member)
+}
+
+/// Rejects or withdraws the pending request of a profile.
+#[instrument(skip(connection))]
+pub async fn delete_group_chat_join_request(
+    group_chat_id: i32,
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<group_chat_join_request::Model, Error> {
+    let target_join_request =
+        get_group_chat_join_request(group_chat_id, profile_id, connection).await?;
+
+    target_join_request.clone().delete(connection).await?;
+
+    info!(
+        "D: Request of profile {:?} to join group chat {:?} has been deleted",
+        profile_id, group_chat_id
+    );
+
+    Ok(target_join_request)
+}
+
+#[instrument(skip(connection))]
+pub async fn delete_join_requests_of_group<C: ConnectionTrait>(
+    group_chat_id: i32,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    Ok(group_chat_join_request::Entity::delete_many()
+        .filter(group_chat_join_request::Column::GroupChatId.eq(group_chat_id))
+        .exec(connection)
+        .await?)
+}
+
+#[instrument(skip(connection))]
+pub async fn delete_join_requests_of_profile<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    Ok(group_chat_join_request::Entity::delete_many()
+        .filter(group_chat_join_request::Column::ProfileId.eq(profile_id))
+        .exec(connection)
+        .await?)
+}
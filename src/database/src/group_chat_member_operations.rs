@@ -1,115 +1,278 @@
-use crate::{check_group_chat_exists, check_profile_exists};
+use crate::{check_group_chat_exists, check_profile_exists, EntityKind, Error};
 use entities::*;
+use log::*;
 use sea_orm::*;
+use std::str::FromStr;
+use tracing::instrument;
 
-pub async fn insert_group_chat_member(
-    profile_id: i32,
-    group_chat_id: i32,
-    connection: &DbConn,
-) -> Result<group_chat_member::Model, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
+/// Role of a member within a group chat, ordered by rank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GroupChatRole {
+    Member,
+    Admin,
+    /// Every group chat with members has exactly one owner
+    Owner,
+}
 
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
+impl GroupChatRole {
+    pub fn name(self) -> &'static str {
+        match self {
+            GroupChatRole::Member => "member",
+            GroupChatRole::Admin => "admin",
+            GroupChatRole::Owner => "owner",
+        }
     }
 
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
+    /// Role of a membership, unknown values are treated as the lowest role.
+    pub fn of(membership: &group_chat_member::Model) -> GroupChatRole {
+        membership.role.parse().unwrap_or(GroupChatRole::Member)
+    }
+}
 
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
+impl FromStr for GroupChatRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "member" => Ok(GroupChatRole::Member),
+            "admin" => Ok(GroupChatRole::Admin),
+            "owner" => Ok(GroupChatRole::Owner),
+            _ => Err(format!("unknown group chat role {:?}", value)),
+        }
+    }
+}
+
+/// Adds a profile to a group chat, every profile can be a member only once.
+#[instrument(skip(connection))]
+pub async fn insert_group_chat_member<C: ConnectionTrait>(
+    profile_id: i32,
+    group_chat_id: i32,
+    role: GroupChatRole,
+    connection: &C,
+) -> Result<group_chat_member::Model, Error> {
+    let target_profile = check_profile_exists(profile_id, connection).await?;
+
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
+
+    match get_group_chat_membership(profile_id, group_chat_id, connection).await {
+        Ok(_) => {
+            return Err(Error::Conflict(
+                "The profile is already a member of this group chat.".to_owned(),
+            ))
+        }
+        Err(Error::NotFound { .. }) => {}
+        Err(err) => return Err(err),
     }
 
     let new_member = group_chat_member::ActiveModel {
-        profile_id: ActiveValue::Set(target_profile.unwrap().profile_id),
-        group_chat_id: ActiveValue::Set(target_group_chat.unwrap().group_chat_id),
+        profile_id: ActiveValue::Set(target_profile.profile_id),
+        group_chat_id: ActiveValue::Set(target_group_chat.group_chat_id),
+        role: ActiveValue::Set(role.name().to_owned()),
         ..Default::default()
     }
     .insert(connection)
-    .await;
+    .await?;
+
+    // a pending request to join is settled once the profile is a member
+    delete_join_request_of_member(&new_member, connection).await?;
+
+    return Ok(new_member);
+}
 
-    return new_member;
+async fn delete_join_request_of_member<C: ConnectionTrait>(
+    member: &group_chat_member::Model,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    Ok(group_chat_join_request::Entity::delete_many()
+        .filter(group_chat_join_request::Column::GroupChatId.eq(member.group_chat_id))
+        .filter(group_chat_join_request::Column::ProfileId.eq(member.profile_id))
+        .exec(connection)
+        .await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn get_members_of_group(
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<Vec<group_chat_member::Model>, DbErr> {
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
-
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
+) -> Result<Vec<group_chat_member::Model>, Error> {
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
     Ok(target_group_chat
-        .unwrap()
         .find_related(group_chat_member::Entity)
         .all(connection)
         .await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn get_memberships_of_profile(
     profile_id: i32,
     connection: &DbConn,
-) -> Result<Vec<group_chat::Model>, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
-
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
-    }
+) -> Result<Vec<group_chat::Model>, Error> {
+    check_profile_exists(profile_id, connection).await?;
 
-    let memberships = target_profile
-        .unwrap()
-        .find_related(group_chat_member::Entity)
+    Ok(group_chat::Entity::find()
+        .inner_join(group_chat_member::Entity)
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
         .all(connection)
+        .await?)
+}
+
+#[instrument(skip(connection))]
+pub async fn is_group_chat_member(
+    profile_id: i32,
+    group_chat_id: i32,
+    connection: &DbConn,
+) -> Result<bool, Error> {
+    let membership_count = group_chat_member::Entity::find()
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+        .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+        .count(connection)
         .await?;
 
-    let mut groups = Vec::new();
+    Ok(membership_count > 0)
+}
 
-    for membership in memberships {
-        let group = membership
-            .find_related(group_chat::Entity)
-            .one(connection)
+#[instrument(skip(connection))]
+pub async fn get_group_chat_membership<C: ConnectionTrait>(
+    profile_id: i32,
+    group_chat_id: i32,
+    connection: &C,
+) -> Result<group_chat_member::Model, Error> {
+    group_chat_member::Entity::find()
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+        .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::GroupChatMember, profile_id))
+}
+
+/// Changes the role of a member and returns every membership which has changed.
+///
+/// Making a member the owner transfers the ownership, the previous owner becomes an admin. The
+/// owner itself can only lose its role this way, so that a group chat is never left without one.
+#[instrument(skip(connection))]
+pub async fn update_group_chat_member_role(
+    group_chat_id: i32,
+    profile_id: i32,
+    role: GroupChatRole,
+    connection: &DbConn,
+) -> Result<Vec<group_chat_member::Model>, Error> {
+    let transaction = connection.begin().await?;
+
+    let target_membership =
+        get_group_chat_membership(profile_id, group_chat_id, &transaction).await?;
+    let current_role = GroupChatRole::of(&target_membership);
+
+    if current_role == role {
+        return Ok(vec![target_membership]);
+    }
+
+    if current_role == GroupChatRole::Owner {
+        return Err(Error::Conflict(
+            "The owner keeps its role until the ownership is transferred to another member."
+                .to_owned(),
+        ));
+    }
+
+    let mut changed_memberships = Vec::new();
+
+    if role == GroupChatRole::Owner {
+        let previous_owners = group_chat_member::Entity::find()
+            .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+            .filter(group_chat_member::Column::Role.eq(GroupChatRole::Owner.name()))
+            .all(&transaction)
             .await?;
 
-        if group.is_none() {
-            continue;
+        for previous_owner in previous_owners {
+            let mut previous_owner: group_chat_member::ActiveModel = previous_owner.into();
+            previous_owner.role = Set(GroupChatRole::Admin.name().to_owned());
+            changed_memberships.push(previous_owner.update(&transaction).await?);
         }
-
-        groups.push(group.unwrap());
     }
 
-    Ok(groups)
+    let mut target_membership: group_chat_member::ActiveModel = target_membership.into();
+    target_membership.role = Set(role.name().to_owned());
+    changed_memberships.push(target_membership.update(&transaction).await?);
+
+    transaction.commit().await?;
+
+    info!(
+        "U: Profile {:?} is now {} of group chat {:?}",
+        profile_id,
+        role.name(),
+        group_chat_id
+    );
+
+    Ok(changed_memberships)
 }
 
+/// Removes a member from a group chat.
+///
+/// If the owner leaves, the ownership passes to the longest-standing admin, or to the
+/// longest-standing member if there is no admin. The new owner is returned in that case.
+#[instrument(skip(connection))]
 pub async fn delete_single_membership(
     group_chat_id: i32,
     profile_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_membership = group_chat_member::Entity::find()
-        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+) -> Result<Option<group_chat_member::Model>, Error> {
+    let transaction = connection.begin().await?;
+
+    let target_membership =
+        get_group_chat_membership(profile_id, group_chat_id, &transaction).await?;
+    let was_owner = GroupChatRole::of(&target_membership) == GroupChatRole::Owner;
+
+    target_membership.delete(&transaction).await?;
+
+    let new_owner = if was_owner {
+        transfer_ownership_to_successor(group_chat_id, &transaction).await?
+    } else {
+        None
+    };
+
+    transaction.commit().await?;
+
+    Ok(new_owner)
+}
+
+async fn transfer_ownership_to_successor<C: ConnectionTrait>(
+    group_chat_id: i32,
+    connection: &C,
+) -> Result<Option<group_chat_member::Model>, Error> {
+    let remaining_members = group_chat_member::Entity::find()
         .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
-        .one(connection)
+        .order_by_asc(group_chat_member::Column::MemberId)
+        .all(connection)
         .await?;
 
-    if target_membership.is_none() {
-        return Err(DbErr::Custom(
-            "Couldn't find a group chat with the specified identifier.".to_owned(),
-        ));
-    }
+    let successor = remaining_members
+        .iter()
+        .find(|member| GroupChatRole::of(member) == GroupChatRole::Admin)
+        .or(remaining_members.first())
+        .cloned();
+
+    let Some(successor) = successor else {
+        return Ok(None);
+    };
+
+    let mut successor: group_chat_member::ActiveModel = successor.into();
+    successor.role = Set(GroupChatRole::Owner.name().to_owned());
+    let successor = successor.update(connection).await?;
+
+    info!(
+        "U: Ownership of group chat {:?} has passed to profile {:?}",
+        group_chat_id, successor.profile_id
+    );
 
-    Ok(target_membership.unwrap().delete(connection).await?)
+    Ok(Some(successor))
 }
 
-pub async fn delete_members_of_group(
+#[instrument(skip(connection))]
+pub async fn delete_members_of_group<C: ConnectionTrait>(
     group_chat_id: i32,
-    connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
-
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    check_group_chat_exists(group_chat_id, connection).await?;
 
     Ok(group_chat_member::Entity::delete_many()
         .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
@@ -117,18 +280,51 @@ pub async fn delete_members_of_group(
         .await?)
 }
 
-pub async fn delete_memberships_of_profile(
-    profile_id: i32,
+/// Removes every member of a group chat except for its owner, so that the group chat keeps
+/// someone who can manage it.
+#[instrument(skip(connection))]
+pub async fn delete_members_of_group_except_owner(
+    group_chat_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
+) -> Result<DeleteResult, Error> {
+    check_group_chat_exists(group_chat_id, connection).await?;
 
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
-    }
+    let deleted_members = group_chat_member::Entity::delete_many()
+        .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+        .filter(group_chat_member::Column::Role.ne(GroupChatRole::Owner.name()))
+        .exec(connection)
+        .await?;
 
-    Ok(group_chat_member::Entity::delete_many()
+    info!(
+        "D: {:?} members have been removed from group chat {:?}",
+        deleted_members.rows_affected, group_chat_id
+    );
+
+    Ok(deleted_members)
+}
+
+/// Removes a profile from all of its group chats, the group chats it owned pass to a successor.
+#[instrument(skip(connection))]
+pub async fn delete_memberships_of_profile<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    check_profile_exists(profile_id, connection).await?;
+
+    let owned_memberships = group_chat_member::Entity::find()
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+        .filter(group_chat_member::Column::Role.eq(GroupChatRole::Owner.name()))
+        .all(connection)
+        .await?;
+
+    let deleted_memberships = group_chat_member::Entity::delete_many()
         .filter(group_chat_member::Column::ProfileId.eq(profile_id))
         .exec(connection)
-        .await?)
+        .await?;
+
+    for owned_membership in owned_memberships {
+        transfer_ownership_to_successor(owned_membership.group_chat_id, connection).await?;
+    }
+
+    Ok(deleted_memberships)
 }
@@ -1,193 +1,242 @@
-use crate::{check_group_chat_exists, check_profile_exists};
+use crate::{
+    check_group_chat_exists, check_profile_exists, mark_group_chat_read, EntityKind, Error,
+    MessageCursor, MessagePage, PagePosition,
+};
+use chrono::Local;
 use entities::*;
+use log::*;
 use sea_orm::*;
+use tracing::instrument;
 
+#[instrument(skip(content, connection))]
 pub async fn insert_group_chat_message(
     author_profile_id: i32,
     group_chat_id: i32,
     content: String,
     connection: &DbConn,
-) -> Result<group_chat_message::Model, DbErr> {
-    let target_profile = check_profile_exists(author_profile_id, connection).await;
+) -> Result<group_chat_message::Model, Error> {
+    let target_profile = check_profile_exists(author_profile_id, connection).await?;
 
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
-    }
-
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
-
-    let group_chat_id = target_group_chat.unwrap().group_chat_id.to_owned();
-    let author_id = target_profile.unwrap().profile_id.to_owned();
+    let group_chat_id = target_group_chat.group_chat_id.to_owned();
+    let author_id = target_profile.profile_id.to_owned();
 
     let new_message = group_chat_message::ActiveModel {
-        author_id: ActiveValue::Set(author_id),
+        author_id: ActiveValue::Set(Some(author_id)),
+        send_time: ActiveValue::Set(Local::now().naive_local()),
         chat_id: ActiveValue::Set(group_chat_id),
         content: ActiveValue::Set(Some(content.to_owned())),
         ..Default::default()
     }
     .insert(connection)
-    .await;
+    .await?;
+
+    // authors have read the chat up to their own message
+    if let Err(err) = mark_group_chat_read(
+        author_id,
+        group_chat_id,
+        Some(new_message.message_id),
+        connection,
+    )
+    .await
+    {
+        warn!(
+            "U: Unable to update the read state of profile {:?}: {}",
+            author_id, err
+        );
+    }
 
-    return new_message;
+    return Ok(new_message);
 }
 
+#[instrument(skip(connection))]
 pub async fn get_group_message_by_id(
     message_id: i32,
     connection: &DbConn,
-) -> Result<group_chat_message::Model, DbErr> {
+) -> Result<group_chat_message::Model, Error> {
     let target_message = group_chat_message::Entity::find_by_id(message_id)
         .one(connection)
         .await?
-        .ok_or(DbErr::Custom(
-            "Couldn't find a group message with the specified identifier.".to_owned(),
-        ));
+        .ok_or(Error::not_found(EntityKind::GroupChatMessage, message_id));
 
     return target_message;
 }
 
+#[instrument(skip(connection))]
 pub async fn get_messages_of_group(
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<Vec<group_chat_message::Model>, DbErr> {
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
-
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
+) -> Result<Vec<group_chat_message::Model>, Error> {
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
     Ok(target_group_chat
-        .unwrap()
         .find_related(group_chat_message::Entity)
         .all(connection)
         .await?)
 }
 
+#[instrument(skip(connection))]
+pub async fn get_messages_of_group_page(
+    group_chat_id: i32,
+    position: PagePosition<MessageCursor>,
+    limit: u64,
+    connection: &DbConn,
+) -> Result<MessagePage<group_chat_message::Model>, Error> {
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
+
+    let query = group_chat_message::Entity::find()
+        .filter(group_chat_message::Column::ChatId.eq(target_group_chat.group_chat_id));
+
+    let query = match position {
+        PagePosition::Latest => query
+            .order_by_desc(group_chat_message::Column::SendTime)
+            .order_by_desc(group_chat_message::Column::MessageId),
+        PagePosition::Before(cursor) => query
+            .filter(
+                Condition::any()
+                    .add(group_chat_message::Column::SendTime.lt(cursor.send_time))
+                    .add(
+                        Condition::all()
+                            .add(group_chat_message::Column::SendTime.eq(cursor.send_time))
+                            .add(group_chat_message::Column::MessageId.lt(cursor.message_id)),
+                    ),
+            )
+            .order_by_desc(group_chat_message::Column::SendTime)
+            .order_by_desc(group_chat_message::Column::MessageId),
+        PagePosition::After(cursor) => query
+            .filter(
+                Condition::any()
+                    .add(group_chat_message::Column::SendTime.gt(cursor.send_time))
+                    .add(
+                        Condition::all()
+                            .add(group_chat_message::Column::SendTime.eq(cursor.send_time))
+                            .add(group_chat_message::Column::MessageId.gt(cursor.message_id)),
+                    ),
+            )
+            .order_by_asc(group_chat_message::Column::SendTime)
+            .order_by_asc(group_chat_message::Column::MessageId),
+    };
+
+    let messages = query.limit(limit + 1).all(connection).await?;
+
+    Ok(MessagePage::from_rows(messages, limit, &position))
+}
+
+#[instrument(skip(connection))]
 pub async fn get_group_messages_of_profile(
     profile_id: i32,
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<Vec<group_chat_message::Model>, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
-
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
-    }
-
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
+) -> Result<Vec<group_chat_message::Model>, Error> {
+    let target_profile = check_profile_exists(profile_id, connection).await?;
 
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
-    let group_chat_id = target_group_chat.unwrap().group_chat_id.to_owned();
-    let author_id = target_profile.unwrap().profile_id.to_owned();
+    let group_chat_id = target_group_chat.group_chat_id.to_owned();
+    let author_id = target_profile.profile_id.to_owned();
 
     let group_messages = group_chat_message::Entity::find()
         .filter(group_chat_message::Column::ChatId.eq(group_chat_id))
         .filter(group_chat_message::Column::AuthorId.eq(author_id))
         .all(connection)
-        .await;
+        .await?;
 
-    return group_messages;
+    return Ok(group_messages);
 }
 
+/// Returns up to `limit` messages of an author across all group chats, ordered by identifier and
+/// starting after the given message.
+#[instrument(skip(connection))]
+pub async fn get_group_messages_of_author_page(
+    author_id: i32,
+    after_message_id: Option<i32>,
+    limit: u64,
+    connection: &DbConn,
+) -> Result<Vec<group_chat_message::Model>, Error> {
+    let mut query = group_chat_message::Entity::find()
+        .filter(group_chat_message::Column::AuthorId.eq(author_id))
+        .order_by_asc(group_chat_message::Column::MessageId);
+
+    if let Some(after_message_id) = after_message_id {
+        query = query.filter(group_chat_message::Column::MessageId.gt(after_message_id));
+    }
+
+    Ok(query.limit(limit).all(connection).await?)
+}
+
+#[instrument(skip(content, connection))]
 pub async fn update_group_message(
     message_id: i32,
     content: String,
     connection: &DbConn,
-) -> Result<group_chat_message::Model, DbErr> {
-    let target_message = get_group_message_by_id(message_id, connection).await;
-
-    if target_message.is_err() {
-        return Err(DbErr::Custom(
-            "Couldn't find a group message with the specified identifier.".to_owned(),
-        ));
-    }
+) -> Result<group_chat_message::Model, Error> {
+    let target_message = get_group_message_by_id(message_id, connection).await?;
 
-    let mut target_message: group_chat_message::ActiveModel = target_message.unwrap().into();
+    let mut target_message: group_chat_message::ActiveModel = target_message.into();
     target_message.content = Set(Some(content.to_owned()));
 
-    target_message.update(connection).await
+    Ok(target_message.update(connection).await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn delete_single_group_message(
     group_chat_message_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_message = group_chat_message::Entity::find()
-        .filter(group_chat_member::Column::ProfileId.eq(group_chat_message_id))
+) -> Result<DeleteResult, Error> {
+    let target_message = group_chat_message::Entity::find_by_id(group_chat_message_id)
         .one(connection)
         .await?;
 
     if target_message.is_none() {
-        return Err(DbErr::Custom(
-            "Couldn't find a group chat with the specified identifier.".to_owned(),
+        return Err(Error::not_found(
+            EntityKind::GroupChatMessage,
+            group_chat_message_id,
         ));
     }
 
     Ok(target_message.unwrap().delete(connection).await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn delete_messages_of_group(
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
+) -> Result<DeleteResult, Error> {
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
-
-    let group_chat_id = target_group_chat.unwrap().group_chat_id.to_owned();
+    let group_chat_id = target_group_chat.group_chat_id.to_owned();
 
     Ok(group_chat_message::Entity::delete_many()
-        .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+        .filter(group_chat_message::Column::ChatId.eq(group_chat_id))
         .exec(connection)
         .await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn delete_group_messages_of_profile(
     profile_id: i32,
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
+) -> Result<DeleteResult, Error> {
+    let target_profile = check_profile_exists(profile_id, connection).await?;
 
-    if target_profile.is_err() {
-        return Err(target_profile.unwrap_err());
-    }
-
-    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await;
+    let target_group_chat = check_group_chat_exists(group_chat_id, connection).await?;
 
-    if target_group_chat.is_err() {
-        return Err(target_group_chat.unwrap_err());
-    }
-
-    let group_chat_id = target_group_chat.unwrap().group_chat_id.to_owned();
-    let author_id = target_profile.unwrap().profile_id.to_owned();
+    let group_chat_id = target_group_chat.group_chat_id.to_owned();
+    let author_id = target_profile.profile_id.to_owned();
 
     Ok(group_chat_message::Entity::delete_many()
-        .filter(group_chat_member::Column::ProfileId.eq(author_id))
+        .filter(group_chat_message::Column::AuthorId.eq(author_id))
         .filter(group_chat_message::Column::ChatId.eq(group_chat_id))
         .exec(connection)
         .await?)
 }
 
+#[instrument(skip(connection))]
 pub async fn check_group_message_exists(
     message_id: i32,
     connection: &DbConn,
-) -> Result<group_chat_message::Model, DbErr> {
-    let target_message = get_group_message_by_id(message_id, connection).await;
-
-    if target_message.is_err() {
-        return Err(DbErr::Custom(
-            "Couldn't find a group chat with the specified identifier.".to_owned(),
-        ));
-    }
-
-    target_message
+) -> Result<group_chat_message::Model, Error> {
+    get_group_message_by_id(message_id, connection).await
 }
@@ -1,135 +1,246 @@
 use crate::{
-    delete_members_of_group, get_profile_by_id,
-    group_chat_member_operations::insert_group_chat_member,
+    delete_invites_of_group, delete_join_requests_of_group, delete_members_of_group,
+    get_profile_by_id, group_chat_member_operations::insert_group_chat_member, EntityKind, Error,
+    GroupChatRole,
 };
 use chrono::Local;
 use entities::*;
 use log::*;
 use sea_orm::*;
+use tracing::instrument;
 
-// TODO: error handling
+/// Maximum length of group chat names in characters, after trimming.
+pub const MAX_GROUP_CHAT_NAME_LENGTH: usize = 64;
+/// Maximum length of group chat descriptions in characters, after trimming.
+pub const MAX_GROUP_CHAT_DESCRIPTION_LENGTH: usize = 1024;
+
+/// Group chat created by [`insert_group_chat`] together with the member IDs which couldn't be added.
+pub struct NewGroupChat {
+    pub group_chat: group_chat::Model,
+    pub rejected_member_ids: Vec<i32>,
+}
+
+/// Changes applied by [`update_group_chat`], fields which are `None` are kept. An empty
+/// description removes the current one.
+#[derive(Default)]
+pub struct GroupChatChanges {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub group_picture: Option<String>,
+    /// Whether joining through an invite has to be approved by an admin
+    pub approval_required: Option<bool>,
+}
+
+/// Trims a group chat name and checks that it is neither empty nor too long.
+fn normalize_group_chat_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+
+    if name.is_empty() || name.chars().count() > MAX_GROUP_CHAT_NAME_LENGTH {
+        return Err(Error::Validation(format!(
+            "Group chat names must contain between 1 and {} characters.",
+            MAX_GROUP_CHAT_NAME_LENGTH
+        )));
+    }
+
+    if name.contains(char::is_control) {
+        return Err(Error::Validation(
+            "Group chat names must not contain line breaks or other control characters.".to_owned(),
+        ));
+    }
+
+    Ok(name.to_owned())
+}
+
+/// Trims a group chat description, an empty description is removed.
+fn normalize_group_chat_description(description: &str) -> Result<Option<String>, Error> {
+    let description = description.trim();
+
+    if description.chars().count() > MAX_GROUP_CHAT_DESCRIPTION_LENGTH {
+        return Err(Error::Validation(format!(
+            "Group chat descriptions may be at most {} characters long.",
+            MAX_GROUP_CHAT_DESCRIPTION_LENGTH
+        )));
+    }
+
+    Ok(Some(description.to_owned()).filter(|description| !description.is_empty()))
+}
+
+/// Creates a group chat and its memberships within a single transaction. Member IDs of profiles
+/// that don't exist are skipped and reported, any other failure rolls back the whole creation.
+#[instrument(skip(name, description, group_picture, connection))]
 pub async fn insert_group_chat(
+    created_by: i32,
+    name: &str,
+    description: Option<&str>,
     member_ids: Vec<i32>,
+    group_picture: Option<String>,
     connection: &DbConn,
-) -> Result<group_chat::Model, DbErr> {
-    if member_ids.len() < 1 {
+) -> Result<NewGroupChat, Error> {
+    let name = normalize_group_chat_name(name)?;
+    let description = match description {
+        Some(description) => normalize_group_chat_description(description)?,
+        None => None,
+    };
+
+    if member_ids.is_empty() {
         warn!("Cannot create a group chat with less than one member");
 
-        return Err(DbErr::Custom(
+        return Err(Error::Validation(
             "A group chat needs at least one member.".to_owned(),
         ));
     }
 
+    let transaction = connection.begin().await?;
+
+    let creation_date = Local::now().naive_local();
+
     let new_group_chat = group_chat::ActiveModel {
-        creation_date: ActiveValue::Set(Local::now().naive_local()),
+        creation_date: ActiveValue::Set(creation_date),
+        group_picture: ActiveValue::Set(group_picture),
+        name: ActiveValue::Set(name),
+        description: ActiveValue::Set(description),
+        created_by: ActiveValue::Set(Some(created_by)),
+        updated_at: ActiveValue::Set(creation_date),
         ..Default::default()
     }
-    .insert(connection)
-    .await
-    .unwrap();
+    .insert(&transaction)
+    .await?;
 
-    let mut new_members = Vec::new();
+    let mut added_member_ids = Vec::new();
+    let mut rejected_member_ids = Vec::new();
 
     for member_id in member_ids {
-        let member_profile = get_profile_by_id(member_id, connection).await;
-
-        if member_profile.is_err() {
+        if added_member_ids.contains(&member_id) || rejected_member_ids.contains(&member_id) {
             continue;
         }
 
-        let group_chat_id = new_group_chat.group_chat_id;
+        match get_profile_by_id(member_id, &transaction).await {
+            Ok(_) => {}
+            Err(Error::NotFound { .. }) => {
+                rejected_member_ids.push(member_id);
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
 
-        let new_member = insert_group_chat_member(
-            member_profile.unwrap().profile_id,
-            group_chat_id,
-            connection,
-        )
-        .await;
+        // the creator owns the group chat, everybody else starts as a member
+        let role = if member_id == created_by {
+            GroupChatRole::Owner
+        } else {
+            GroupChatRole::Member
+        };
 
-        new_members.push(new_member.unwrap());
+        insert_group_chat_member(member_id, new_group_chat.group_chat_id, role, &transaction)
+            .await?;
+        added_member_ids.push(member_id);
     }
 
-    if new_members.len() < 1 {
+    if added_member_ids.is_empty() {
         warn!(
             "Cannot create a group chat with less than one member. All given profile were faulty"
         );
 
-        return Err(DbErr::Custom(
+        // dropping the transaction rolls back the group chat row
+        return Err(Error::Validation(
             "A group chat needs at least one member. All given profiles couldn't be resolved."
                 .to_owned(),
         ));
     }
 
-    return Ok(new_group_chat);
+    transaction.commit().await?;
+
+    info!(
+        "C: New group chat has been created: {:?}",
+        new_group_chat.group_chat_id
+    );
+
+    return Ok(NewGroupChat {
+        group_chat: new_group_chat,
+        rejected_member_ids,
+    });
 }
 
+/// Applies the given changes to a group chat and bumps its `updated_at`.
+#[instrument(skip(changes, connection))]
 pub async fn update_group_chat(
     group_chat_id: i32,
-    group_picture: String,
+    changes: GroupChatChanges,
     connection: &DbConn,
-) -> Result<group_chat::Model, DbErr> {
+) -> Result<group_chat::Model, Error> {
     let target_group_chat = get_group_chat_by_id(group_chat_id, connection).await;
 
     match target_group_chat {
         Ok(target_group_chat) => {
             let mut target_group_chat: group_chat::ActiveModel = target_group_chat.into();
-            target_group_chat.group_picture = Set(Some(group_picture));
+
+            if let Some(name) = changes.name {
+                target_group_chat.name = Set(normalize_group_chat_name(&name)?);
+            }
+            if let Some(description) = changes.description {
+                target_group_chat.description =
+                    Set(normalize_group_chat_description(&description)?);
+            }
+            if let Some(group_picture) = changes.group_picture {
+                target_group_chat.group_picture = Set(Some(group_picture));
+            }
+            if let Some(approval_required) = changes.approval_required {
+                target_group_chat.approval_required = Set(approval_required);
+            }
+            target_group_chat.updated_at = Set(Local::now().naive_local());
 
             Ok(target_group_chat.update(connection).await?)
         }
-        _ => {
-            warn!("Group chat with ID {:?} does not exist", group_chat_id);
-            Err(DbErr::Custom(
-                "Couldn't find a group chat with the specified identifier.".to_owned(),
-            ))
+        Err(err) => {
+            warn!("U: Group chat with ID {:?} does not exist", group_chat_id);
+            Err(err)
         }
     }
 }
 
-pub async fn get_group_chat_by_id(
+#[instrument(skip(connection))]
+pub async fn get_group_chat_by_id<C: ConnectionTrait>(
     group_chat_id: i32,
-    connection: &DbConn,
-) -> Result<group_chat::Model, DbErr> {
+    connection: &C,
+) -> Result<group_chat::Model, Error> {
     let target_group_chat = group_chat::Entity::find_by_id(group_chat_id)
         .one(connection)
         .await?
-        .ok_or(DbErr::Custom(
-            "Couldn't find a group chat with the specified identifier.".to_owned(),
-        ));
+        .ok_or(Error::not_found(EntityKind::GroupChat, group_chat_id));
 
     return target_group_chat;
 }
 
+/// Deletes a group chat together with its messages, memberships, invites and join requests within
+/// a single transaction.
+#[instrument(skip(connection))]
 pub async fn delete_group_chat_by_id(
     group_chat_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let delete_result = delete_members_of_group(group_chat_id, connection).await;
+) -> Result<DeleteResult, Error> {
+    let transaction = connection.begin().await?;
 
-    if delete_result.is_err() {
-        return Err(DbErr::Custom(
-            "Couldn't delete any group chat members.".to_owned(),
-        ));
-    }
+    group_chat_message::Entity::delete_many()
+        .filter(group_chat_message::Column::ChatId.eq(group_chat_id))
+        .exec(&transaction)
+        .await?;
+
+    delete_members_of_group(group_chat_id, &transaction).await?;
+    delete_join_requests_of_group(group_chat_id, &transaction).await?;
+    delete_invites_of_group(group_chat_id, &transaction).await?;
 
     let target_group_chat = group_chat::Entity::delete_by_id(group_chat_id)
-        .exec(connection)
+        .exec(&transaction)
         .await?;
 
+    transaction.commit().await?;
+
     return Ok(target_group_chat);
 }
 
-pub async fn check_group_chat_exists(
+#[instrument(skip(connection))]
+pub async fn check_group_chat_exists<C: ConnectionTrait>(
     group_chat_id: i32,
-    connection: &DbConn,
-) -> Result<group_chat::Model, DbErr> {
-    let target_group_chat = get_group_chat_by_id(group_chat_id, connection).await;
-
-    if target_group_chat.is_err() {
-        return Err(DbErr::Custom(
-            "Couldn't find a group chat with the specified identifier.".to_owned(),
-        ));
-    }
-
-    target_group_chat
+    connection: &C,
+) -> Result<group_chat::Model, Error> {
+    get_group_chat_by_id(group_chat_id, connection).await
 }
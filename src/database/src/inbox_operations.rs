@@ -0,0 +1,271 @@
+use crate::{
+    get_memberships_of_profile, get_private_chats_of_profile, private_chat_last_read_message_id,
+    Error,
+};
+use entities::*;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use std::collections::HashMap;
+use tracing::instrument;
+
+/// A group chat of a profile together with its most recent message.
+#[derive(Clone, Debug)]
+pub struct GroupChatSummary {
+    pub group_chat: group_chat::Model,
+    pub last_message: Option<group_chat_message::Model>,
+    pub unread_count: u64,
+}
+
+/// A private chat of a profile together with its most recent message.
+#[derive(Clone, Debug)]
+pub struct PrivateChatSummary {
+    pub private_chat: private_chat::Model,
+    pub last_message: Option<private_message::Model>,
+    pub unread_count: u64,
+}
+
+/// All chats a profile participates in.
+#[derive(Clone, Debug)]
+pub struct Inbox {
+    pub group_chats: Vec<GroupChatSummary>,
+    pub private_chats: Vec<PrivateChatSummary>,
+}
+
+#[derive(FromQueryResult)]
+struct ChatMessageId {
+    chat_id: i32,
+    message_id: i32,
+}
+
+#[derive(FromQueryResult)]
+struct ChatMessageCount {
+    chat_id: i32,
+    message_count: i64,
+}
+
+/// Collects the chats of a profile with their last message and unread count.
+///
+/// The number of queries doesn't depend on the number of chats. Messages of other participants
+/// count as unread when they are newer than the read cursor of the profile in that chat.
+#[instrument(skip(connection))]
+pub async fn get_inbox_of_profile(profile_id: i32, connection: &DbConn) -> Result<Inbox, Error> {
+    Ok(Inbox {
+        group_chats: get_group_chat_summaries(profile_id, connection).await?,
+        private_chats: get_private_chat_summaries(profile_id, connection).await?,
+    })
+}
+
+#[instrument(skip(connection))]
+async fn get_group_chat_summaries(
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<Vec<GroupChatSummary>, Error> {
+    let group_chats = get_memberships_of_profile(profile_id, connection).await?;
+
+    if group_chats.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let group_chat_ids: Vec<i32> = group_chats
+        .iter()
+        .map(|group_chat| group_chat.group_chat_id)
+        .collect();
+
+    let last_message_ids = latest_group_chat_message_ids(&group_chat_ids, connection).await?;
+    let mut last_messages: HashMap<i32, group_chat_message::Model> =
+        group_chat_message::Entity::find()
+            .filter(
+                group_chat_message::Column::MessageId
+                    .is_in(last_message_ids.values().copied().collect::<Vec<i32>>()),
+            )
+            .all(connection)
+            .await?
+            .into_iter()
+            .map(|message| (message.chat_id, message))
+            .collect();
+
+    let read_message_ids: HashMap<i32, i32> = group_chat_member::Entity::find()
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+        .all(connection)
+        .await?
+        .into_iter()
+        .filter_map(|membership| {
+            membership
+                .last_read_message_id
+                .map(|message_id| (membership.group_chat_id, message_id))
+        })
+        .collect();
+
+    let unread_condition = group_chat_ids
+        .iter()
+        .fold(Condition::any(), |condition, id| {
+            condition.add(
+                Condition::all()
+                    .add(group_chat_message::Column::ChatId.eq(*id))
+                    .add(
+                        group_chat_message::Column::MessageId
+                            .gt(read_message_ids.get(id).copied().unwrap_or(0)),
+                    ),
+            )
+        });
+
+    let unread_counts: HashMap<i32, u64> = group_chat_message::Entity::find()
+        .select_only()
+        .column_as(group_chat_message::Column::ChatId, "chat_id")
+        .column_as(
+            Expr::col(group_chat_message::Column::MessageId).count(),
+            "message_count",
+        )
+        // messages of erased profiles have no author anymore, but are still unread
+        .filter(
+            group_chat_message::Column::AuthorId
+                .ne(profile_id)
+                .or(group_chat_message::Column::AuthorId.is_null()),
+        )
+        .filter(unread_condition)
+        .group_by(group_chat_message::Column::ChatId)
+        .into_model::<ChatMessageCount>()
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|count| (count.chat_id, count.message_count as u64))
+        .collect();
+
+    Ok(group_chats
+        .into_iter()
+        .map(|group_chat| GroupChatSummary {
+            last_message: last_messages.remove(&group_chat.group_chat_id),
+            unread_count: unread_counts
+                .get(&group_chat.group_chat_id)
+                .copied()
+                .unwrap_or(0),
+            group_chat,
+        })
+        .collect())
+}
+
+/// Returns the identifier of the newest message per group chat.
+#[instrument(skip(connection))]
+async fn latest_group_chat_message_ids(
+    group_chat_ids: &[i32],
+    connection: &DbConn,
+) -> Result<HashMap<i32, i32>, Error> {
+    let query = group_chat_message::Entity::find()
+        .select_only()
+        .column_as(group_chat_message::Column::ChatId, "chat_id")
+        .column_as(
+            Expr::col(group_chat_message::Column::MessageId).max(),
+            "message_id",
+        )
+        .filter(group_chat_message::Column::ChatId.is_in(group_chat_ids.to_vec()))
+        .group_by(group_chat_message::Column::ChatId);
+
+    Ok(query
+        .into_model::<ChatMessageId>()
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|row| (row.chat_id, row.message_id))
+        .collect())
+}
+
+#[instrument(skip(connection))]
+async fn get_private_chat_summaries(
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<Vec<PrivateChatSummary>, Error> {
+    let private_chats = get_private_chats_of_profile(profile_id, connection).await?;
+
+    if private_chats.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let private_chat_ids: Vec<i32> = private_chats
+        .iter()
+        .map(|private_chat| private_chat.private_chat_id)
+        .collect();
+
+    let last_message_ids = latest_private_message_ids(&private_chat_ids, connection).await?;
+    let mut last_messages: HashMap<i32, private_message::Model> = private_message::Entity::find()
+        .filter(
+            private_message::Column::PrivateMessageId
+                .is_in(last_message_ids.values().copied().collect::<Vec<i32>>()),
+        )
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|message| (message.private_chat_id, message))
+        .collect();
+
+    let unread_condition =
+        private_chats
+            .iter()
+            .fold(Condition::any(), |condition, private_chat| {
+                condition.add(
+                    Condition::all()
+                        .add(
+                            private_message::Column::PrivateChatId.eq(private_chat.private_chat_id),
+                        )
+                        .add(
+                            private_message::Column::PrivateMessageId
+                                .gt(private_chat_last_read_message_id(private_chat, profile_id)
+                                    .unwrap_or(0)),
+                        ),
+                )
+            });
+
+    let unread_counts: HashMap<i32, u64> = private_message::Entity::find()
+        .select_only()
+        .column_as(private_message::Column::PrivateChatId, "chat_id")
+        .column_as(
+            Expr::col(private_message::Column::PrivateMessageId).count(),
+            "message_count",
+        )
+        .filter(private_message::Column::RecipientId.eq(profile_id))
+        .filter(private_message::Column::DeletedAt.is_null())
+        .filter(unread_condition)
+        .group_by(private_message::Column::PrivateChatId)
+        .into_model::<ChatMessageCount>()
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|count| (count.chat_id, count.message_count as u64))
+        .collect();
+
+    Ok(private_chats
+        .into_iter()
+        .map(|private_chat| PrivateChatSummary {
+            last_message: last_messages.remove(&private_chat.private_chat_id),
+            unread_count: unread_counts
+                .get(&private_chat.private_chat_id)
+                .copied()
+                .unwrap_or(0),
+            private_chat,
+        })
+        .collect())
+}
+
+/// Returns the identifier of the newest message per private chat.
+#[instrument(skip(connection))]
+async fn latest_private_message_ids(
+    private_chat_ids: &[i32],
+    connection: &DbConn,
+) -> Result<HashMap<i32, i32>, Error> {
+    let query = private_message::Entity::find()
+        .select_only()
+        .column_as(private_message::Column::PrivateChatId, "chat_id")
+        .column_as(
+            Expr::col(private_message::Column::PrivateMessageId).max(),
+            "message_id",
+        )
+        .filter(private_message::Column::PrivateChatId.is_in(private_chat_ids.to_vec()))
+        .group_by(private_message::Column::PrivateChatId);
+
+    Ok(query
+        .into_model::<ChatMessageId>()
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(|row| (row.chat_id, row.message_id))
+        .collect())
+}
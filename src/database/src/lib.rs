@@ -1,15 +1,35 @@
 mod connection;
+mod erasure_operations;
+mod error;
+mod group_chat_invite_operations;
+mod group_chat_join_request_operations;
 mod group_chat_member_operations;
 mod group_chat_operations;
 mod group_chat_message_operations;
+mod inbox_operations;
+mod pagination;
+mod password;
+mod private_chat_operations;
 mod private_message_operations;
 mod profile_operations;
+mod read_state_operations;
+mod session_operations;
 
 pub use connection::*;
+pub use erasure_operations::*;
+pub use error::*;
+pub use group_chat_invite_operations::*;
+pub use group_chat_join_request_operations::*;
 pub use group_chat_member_operations::*;
 pub use group_chat_operations::*;
 pub use group_chat_message_operations::*;
+pub use inbox_operations::*;
+pub use pagination::*;
+pub use password::*;
+pub use private_chat_operations::*;
 pub use private_message_operations::*;
 pub use profile_operations::*;
+pub use read_state_operations::*;
+pub use session_operations::*;
 
 pub use sea_orm;
@@ -0,0 +1,53 @@
+use chrono::NaiveDateTime;
+
+pub const DEFAULT_PAGE_SIZE: u64 = 50;
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// Position of a group chat message in the history, which is ordered by send time and identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageCursor {
+    pub send_time: NaiveDateTime,
+    pub message_id: i32,
+}
+
+/// Part of a message history which should be loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PagePosition<C> {
+    /// Most recent messages
+    Latest,
+    /// Messages older than the cursor
+    Before(C),
+    /// Messages newer than the cursor
+    After(C),
+}
+
+/// Messages in chronological order, plus whether further messages exist in the requested direction.
+#[derive(Clone, Debug)]
+pub struct MessagePage<T> {
+    pub messages: Vec<T>,
+    pub has_more: bool,
+}
+
+impl<T> MessagePage<T> {
+    /// Builds a page from a query result which fetched up to `limit + 1` rows.
+    ///
+    /// Rows are expected in the query order, which is descending unless `After` was requested.
+    pub(crate) fn from_rows<C>(mut rows: Vec<T>, limit: u64, position: &PagePosition<C>) -> Self {
+        let has_more = rows.len() as u64 > limit;
+        rows.truncate(limit as usize);
+
+        if !matches!(position, PagePosition::After(_)) {
+            rows.reverse();
+        }
+
+        MessagePage {
+            messages: rows,
+            has_more,
+        }
+    }
+}
+
+/// Clamps a requested page size to the allowed range.
+pub fn page_size(limit: Option<u64>) -> u64 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
@@ -0,0 +1,143 @@
+use crate::Error;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use log::*;
+use sea_orm::DbErr;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Argon2id cost parameters of new password hashes, defaulting to the recommendations of the
+/// `argon2` crate.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordConfig {
+    /// Memory cost in KiB
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        PasswordConfig {
+            memory_cost: Params::DEFAULT_M_COST,
+            time_cost: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordConfig {
+    /// Checks the parameters against the limits of Argon2id.
+    pub fn validate(&self) -> Result<(), String> {
+        self.params().map(|_| ()).map_err(|err| err.to_string())
+    }
+
+    fn params(&self) -> Result<Params, argon2::Error> {
+        Params::new(self.memory_cost, self.time_cost, self.parallelism, None)
+    }
+}
+
+fn password_hasher(config: &PasswordConfig) -> Result<Argon2<'static>, Error> {
+    let params = config.params().map_err(|err| {
+        error!("Invalid password hashing parameters: {}", err);
+        Error::Db(DbErr::Custom(
+            "Invalid password hashing parameters.".to_owned(),
+        ))
+    })?;
+
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+/// Hashes a plaintext password with Argon2id and a random per-user salt.
+///
+/// The result is a PHC string which contains the algorithm, parameters and salt.
+pub fn hash_password(password: &str, config: &PasswordConfig) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let password_hash = password_hasher(config)?
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| {
+            error!("Unable to hash password: {}", err);
+            Error::Db(DbErr::Custom("Unable to hash password.".to_owned()))
+        })?;
+
+    Ok(password_hash.to_string())
+}
+
+/// Hash of "dummy password" with the default parameters, verified for unknown usernames.
+const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$gIhIYQkFZ1LjWzb3i1vd9A$6OQvM9S5tmxzbE+4SkRsNjUX1qqXmSggDZuoWQBEiGo";
+
+/// Checks a plaintext password against a stored PHC string.
+///
+/// Stored values which are not valid PHC strings never match.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    let parsed_hash = match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => parsed_hash,
+        Err(err) => {
+            warn!("R: Stored password hash is invalid: {}", err);
+            return false;
+        }
+    };
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok()
+}
+
+/// Returns whether a stored password predates hashing and is still kept in plaintext.
+///
+/// PHC strings always start with `$`, so values starting with it are treated as (possibly
+/// corrupted) hashes and never as plaintext.
+pub fn is_legacy_password(stored_password: &str) -> bool {
+    !stored_password.starts_with('$')
+}
+
+/// Compares a password in constant time with a stored plaintext one, see
+/// [`is_legacy_password`]. Only used on login, which upgrades the stored password right after.
+pub fn verify_legacy_password(password: &str, stored_password: &str) -> bool {
+    is_legacy_password(stored_password) && digests_match(password, stored_password)
+}
+
+/// Verifies a password against a fixed hash, so that logins with unknown usernames take as long
+/// as logins with wrong passwords.
+pub fn verify_dummy_password(password: &str) {
+    verify_password(password, DUMMY_HASH);
+}
+
+fn digests_match(left: &str, right: &str) -> bool {
+    let left = Sha256::digest(left.as_bytes());
+    let right = Sha256::digest(right.as_bytes());
+
+    left.iter()
+        .zip(right.iter())
+        .fold(0, |difference, (left, right)| difference | (left ^ right))
+        == 0
+}
+
+/// Returns whether a stored hash was created with other parameters than the current ones.
+pub fn password_needs_rehash(password_hash: &str, config: &PasswordConfig) -> bool {
+    let parsed_hash = match PasswordHash::new(password_hash) {
+        Ok(parsed_hash) => parsed_hash,
+        Err(_) => return true,
+    };
+
+    if parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    let (stored_params, current_hasher) =
+        match (Params::try_from(&parsed_hash), password_hasher(config)) {
+            (Ok(stored_params), Ok(current_hasher)) => (stored_params, current_hasher),
+            _ => return true,
+        };
+    let current_params = current_hasher.params();
+
+    stored_params.m_cost() != current_params.m_cost()
+        || stored_params.t_cost() != current_params.t_cost()
+        || stored_params.p_cost() != current_params.p_cost()
+}
@@ -0,0 +1,103 @@
+use crate::{check_profile_exists, EntityKind, Error};
+use chrono::Local;
+use entities::*;
+use log::*;
+use sea_orm::*;
+use tracing::instrument;
+
+/// Orders a participant pair, so that it matches the stored `first_profile_id`/`second_profile_id`.
+fn ordered_participants(profile_id: i32, other_profile_id: i32) -> (i32, i32) {
+    if profile_id <= other_profile_id {
+        (profile_id, other_profile_id)
+    } else {
+        (other_profile_id, profile_id)
+    }
+}
+
+/// Returns the private chat of two profiles and creates it if they haven't talked before.
+#[instrument(skip(connection))]
+pub async fn get_or_insert_private_chat(
+    profile_id: i32,
+    other_profile_id: i32,
+    connection: &DbConn,
+) -> Result<private_chat::Model, Error> {
+    if let Some(private_chat) =
+        get_private_chat_of_participants(profile_id, other_profile_id, connection).await?
+    {
+        return Ok(private_chat);
+    }
+
+    // the participants are checked beforehand, so that a missing one is reported as such
+    check_profile_exists(profile_id, connection).await?;
+    check_profile_exists(other_profile_id, connection).await?;
+
+    let (first_profile_id, second_profile_id) = ordered_participants(profile_id, other_profile_id);
+
+    let new_private_chat = private_chat::ActiveModel {
+        first_profile_id: ActiveValue::Set(Some(first_profile_id)),
+        second_profile_id: ActiveValue::Set(Some(second_profile_id)),
+        creation_date: ActiveValue::Set(Local::now().naive_local()),
+        ..Default::default()
+    }
+    .insert(connection)
+    .await;
+
+    match new_private_chat {
+        Ok(private_chat) => {
+            info!(
+                "C: New private chat has been created: {:?}",
+                private_chat.private_chat_id
+            );
+            Ok(private_chat)
+        }
+        Err(err) => {
+            // a concurrent request may have created the chat in the meantime
+            warn!("C: Unable to create a new private chat: {}", err);
+            get_private_chat_of_participants(profile_id, other_profile_id, connection)
+                .await?
+                .ok_or(err.into())
+        }
+    }
+}
+
+#[instrument(skip(connection))]
+pub async fn get_private_chat_by_id(
+    private_chat_id: i32,
+    connection: &DbConn,
+) -> Result<private_chat::Model, Error> {
+    private_chat::Entity::find_by_id(private_chat_id)
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::PrivateChat, private_chat_id))
+}
+
+#[instrument(skip(connection))]
+pub async fn get_private_chat_of_participants(
+    profile_id: i32,
+    other_profile_id: i32,
+    connection: &DbConn,
+) -> Result<Option<private_chat::Model>, Error> {
+    let (first_profile_id, second_profile_id) = ordered_participants(profile_id, other_profile_id);
+
+    Ok(private_chat::Entity::find()
+        .filter(private_chat::Column::FirstProfileId.eq(first_profile_id))
+        .filter(private_chat::Column::SecondProfileId.eq(second_profile_id))
+        .one(connection)
+        .await?)
+}
+
+#[instrument(skip(connection))]
+pub async fn get_private_chats_of_profile(
+    profile_id: i32,
+    connection: &DbConn,
+) -> Result<Vec<private_chat::Model>, Error> {
+    Ok(private_chat::Entity::find()
+        .filter(
+            Condition::any()
+                .add(private_chat::Column::FirstProfileId.eq(profile_id))
+                .add(private_chat::Column::SecondProfileId.eq(profile_id)),
+        )
+        .order_by_desc(private_chat::Column::CreationDate)
+        .all(connection)
+        .await?)
+}
@@ -1,17 +1,26 @@
+use crate::{get_or_insert_private_chat, get_private_chat_of_participants, mark_private_chat_read};
+use crate::{EntityKind, Error, MessageCursor, MessagePage, PagePosition};
+use chrono::Local;
 use entities::*;
 use log::*;
 use sea_orm::*;
+use tracing::instrument;
 
+#[instrument(skip(content, connection))]
 pub async fn insert_private_message(
     sender_id: i32,
     recipient_id: i32,
     content: Option<String>,
     connection: &DbConn,
-) -> Result<private_message::Model, DbErr> {
+) -> Result<private_message::Model, Error> {
+    let private_chat = get_or_insert_private_chat(sender_id, recipient_id, connection).await?;
+
     let new_message = private_message::ActiveModel {
-        sender_id: ActiveValue::Set(sender_id),
-        recipient_id: ActiveValue::Set(recipient_id),
+        private_chat_id: ActiveValue::Set(private_chat.private_chat_id),
+        sender_id: ActiveValue::Set(Some(sender_id)),
+        recipient_id: ActiveValue::Set(Some(recipient_id)),
         content: ActiveValue::Set(content.to_owned()),
+        sent_at: ActiveValue::Set(Local::now().naive_local()),
         ..Default::default()
     }
     .insert(connection)
@@ -23,80 +32,206 @@ pub async fn insert_private_message(
                 "C: New private message has been created: {:?}",
                 message.private_message_id
             );
+
+            // senders have read the chat up to their own message
+            if let Err(err) = mark_private_chat_read(
+                sender_id,
+                message.private_chat_id,
+                Some(message.private_message_id),
+                connection,
+            )
+            .await
+            {
+                warn!(
+                    "U: Unable to update the read state of profile {:?}: {}",
+                    sender_id, err
+                );
+            }
+
             return Ok(message);
         }
         Err(err) => {
             warn!("C: Unable to create a new private message: {}", err);
-            return Err(err);
+            return Err(err.into());
         }
     }
 }
 
+#[instrument(skip(content, connection))]
 pub async fn update_private_message(
     message_id: i32,
     content: String,
     connection: &DbConn,
-) -> Result<private_message::Model, DbErr> {
-    let target_message = get_private_message_by_id(message_id, connection).await;
+) -> Result<private_message::Model, Error> {
+    let target_message = get_private_message_by_id(message_id, connection)
+        .await
+        .inspect_err(|_| {
+            warn!("U: Private message with ID {:?} does not exist", message_id);
+        })?;
 
-    if target_message.is_err() {
-        warn!("U: Private message with ID {:?} does not exist", message_id);
-        return Err(target_message.unwrap_err());
+    if target_message.deleted_at.is_some() {
+        warn!(
+            "U: Private message with ID {:?} has been deleted",
+            message_id
+        );
+        return Err(Error::Conflict(
+            "Deleted messages cannot be edited.".to_owned(),
+        ));
     }
 
-    let mut target_message: private_message::ActiveModel = target_message.unwrap().into();
+    let mut target_message: private_message::ActiveModel = target_message.into();
     target_message.content = Set(Some(content.to_owned()));
+    target_message.edited_at = Set(Some(Local::now().naive_local()));
 
     Ok(target_message.update(connection).await?)
 }
 
+/// Returns the messages of both participants of a private chat.
+#[instrument(skip(connection))]
 pub async fn get_private_messages_of_chat(
     sender_id: i32,
     recipient_id: i32,
     connection: &DbConn,
-) -> Result<Vec<private_message::Model>, DbErr> {
+) -> Result<Vec<private_message::Model>, Error> {
+    let private_chat =
+        match get_private_chat_of_participants(sender_id, recipient_id, connection).await? {
+            Some(private_chat) => private_chat,
+            None => return Ok(vec![]),
+        };
+
     let target_messages = private_message::Entity::find()
-        .filter(private_message::Column::SenderId.eq(sender_id))
-        .filter(private_message::Column::RecipientId.eq(recipient_id))
+        .filter(private_message::Column::PrivateChatId.eq(private_chat.private_chat_id))
+        .order_by_asc(private_message::Column::SentAt)
+        .order_by_asc(private_message::Column::PrivateMessageId)
         .all(connection)
         .await?;
 
     return Ok(target_messages);
 }
 
+/// Returns a page of the messages of both participants of a private chat.
+#[instrument(skip(connection))]
+pub async fn get_private_messages_of_chat_page(
+    sender_id: i32,
+    recipient_id: i32,
+    position: PagePosition<MessageCursor>,
+    limit: u64,
+    connection: &DbConn,
+) -> Result<MessagePage<private_message::Model>, Error> {
+    match get_private_chat_of_participants(sender_id, recipient_id, connection).await? {
+        Some(private_chat) => {
+            get_messages_of_private_chat_page(
+                private_chat.private_chat_id,
+                position,
+                limit,
+                connection,
+            )
+            .await
+        }
+        None => Ok(MessagePage {
+            messages: vec![],
+            has_more: false,
+        }),
+    }
+}
+
+#[instrument(skip(connection))]
+pub async fn get_messages_of_private_chat_page(
+    private_chat_id: i32,
+    position: PagePosition<MessageCursor>,
+    limit: u64,
+    connection: &DbConn,
+) -> Result<MessagePage<private_message::Model>, Error> {
+    let query = private_message::Entity::find()
+        .filter(private_message::Column::PrivateChatId.eq(private_chat_id));
+
+    let query = match position {
+        PagePosition::Latest => query
+            .order_by_desc(private_message::Column::SentAt)
+            .order_by_desc(private_message::Column::PrivateMessageId),
+        PagePosition::Before(cursor) => query
+            .filter(
+                Condition::any()
+                    .add(private_message::Column::SentAt.lt(cursor.send_time))
+                    .add(
+                        Condition::all()
+                            .add(private_message::Column::SentAt.eq(cursor.send_time))
+                            .add(private_message::Column::PrivateMessageId.lt(cursor.message_id)),
+                    ),
+            )
+            .order_by_desc(private_message::Column::SentAt)
+            .order_by_desc(private_message::Column::PrivateMessageId),
+        PagePosition::After(cursor) => query
+            .filter(
+                Condition::any()
+                    .add(private_message::Column::SentAt.gt(cursor.send_time))
+                    .add(
+                        Condition::all()
+                            .add(private_message::Column::SentAt.eq(cursor.send_time))
+                            .add(private_message::Column::PrivateMessageId.gt(cursor.message_id)),
+                    ),
+            )
+            .order_by_asc(private_message::Column::SentAt)
+            .order_by_asc(private_message::Column::PrivateMessageId),
+    };
+
+    let messages = query.limit(limit + 1).all(connection).await?;
+
+    Ok(MessagePage::from_rows(messages, limit, &position))
+}
+
+#[instrument(skip(connection))]
 pub async fn get_private_message_by_id(
     message_id: i32,
     connection: &DbConn,
-) -> Result<private_message::Model, DbErr> {
+) -> Result<private_message::Model, Error> {
     let target_message = private_message::Entity::find_by_id(message_id)
         .one(connection)
         .await?
-        .ok_or(DbErr::Custom(
-            "Couldn't find a message with the specified identifier.".to_owned(),
-        ));
+        .ok_or(Error::not_found(EntityKind::PrivateMessage, message_id));
 
     return target_message;
 }
 
+/// Marks a private message as deleted and removes its content.
+///
+/// The row is kept, so that clients can still render a placeholder in the chat history.
+#[instrument(skip(connection))]
 pub async fn delete_private_message_by_id(
     private_message_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let delete_result = private_message::Entity::delete_by_id(private_message_id)
-        .exec(connection)
-        .await?;
+) -> Result<private_message::Model, Error> {
+    let target_message = get_private_message_by_id(private_message_id, connection)
+        .await
+        .inspect_err(|_| {
+            warn!(
+                "D: Private message with ID {:?} does not exist",
+                private_message_id
+            );
+        })?;
 
-    return Ok(delete_result);
+    let mut target_message: private_message::ActiveModel = target_message.into();
+    target_message.content = Set(None);
+    target_message.deleted_at = Set(Some(Local::now().naive_local()));
+
+    Ok(target_message.update(connection).await?)
 }
 
+/// Deletes the messages of both participants of a private chat.
+#[instrument(skip(connection))]
 pub async fn delete_private_messages_of_chat(
     sender_id: i32,
     recipient_id: i32,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
+) -> Result<DeleteResult, Error> {
+    let private_chat =
+        match get_private_chat_of_participants(sender_id, recipient_id, connection).await? {
+            Some(private_chat) => private_chat,
+            None => return Ok(DeleteResult { rows_affected: 0 }),
+        };
+
     let delete_result = private_message::Entity::delete_many()
-        .filter(private_message::Column::SenderId.eq(sender_id))
-        .filter(private_message::Column::RecipientId.eq(recipient_id))
+        .filter(private_message::Column::PrivateChatId.eq(private_chat.private_chat_id))
         .exec(connection)
         .await?;
 
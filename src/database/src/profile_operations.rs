@@ -1,19 +1,26 @@
+use crate::{
+    hash_password, password_needs_rehash, verify_dummy_password, verify_legacy_password,
+    verify_password, EntityKind, Error, PasswordConfig,
+};
 use chrono::Local;
 use entities::*;
 use log::*;
 use sea_orm::*;
+use tracing::instrument;
 
 // TODO: profile picture
+#[instrument(skip(username, displayname, hashed_password, email_address, connection))]
 pub async fn insert_profile(
     username: &str,
     displayname: &Option<String>,
     hashed_password: &str,
     email_address: &str,
     connection: &DbConn,
-) -> Result<profile::ActiveModel, DbErr> {
-    if !is_username_valid(&username) {
-        return Err(DbErr::Custom(
-            "Whitespaces cannot be used in usernames!".to_string(),
+) -> Result<profile::ActiveModel, Error> {
+    if !is_username_valid(username) {
+        return Err(Error::Validation(
+            "Usernames must not contain whitespaces and may be at most 32 characters long."
+                .to_owned(),
         ));
     }
 
@@ -31,16 +38,27 @@ pub async fn insert_profile(
 
     match new_profile {
         Ok(profile) => {
-            info!("C: New profile has been created: {:?}", profile.profile_id);
+            info!(
+                "C: New profile has been created: {}",
+                profile.profile_id.as_ref()
+            );
             return Ok(profile);
         }
         Err(err) => {
             warn!("C: Unable to create a new profile: {}", err);
-            return Err(err);
+            return Err(err.into());
         }
     }
 }
 
+#[instrument(skip(
+    username,
+    displayname,
+    hashed_password,
+    email_address,
+    profile_picture,
+    connection
+))]
 pub async fn update_profile(
     profile_id: i32,
     username: &str,
@@ -49,84 +67,114 @@ pub async fn update_profile(
     email_address: &str,
     profile_picture: &str,
     connection: &DbConn,
-) -> Result<profile::Model, DbErr> {
-    let target_profile = check_profile_exists(profile_id, connection).await;
-
-    if target_profile.is_err() {
-        warn!("U: Profile with ID {:?} does not exist", profile_id);
-        return Err(target_profile.unwrap_err());
+) -> Result<profile::Model, Error> {
+    let target_profile = check_profile_exists(profile_id, connection)
+        .await
+        .inspect_err(|_| {
+            warn!("U: Profile with ID {:?} does not exist", profile_id);
+        })?;
+
+    if !is_username_valid(username) {
+        return Err(Error::Validation(
+            "Usernames must not contain whitespaces and may be at most 32 characters long."
+                .to_owned(),
+        ));
     }
 
-    let target_profile = target_profile.unwrap();
+    let mut target_profile: profile::ActiveModel = target_profile.into();
+    target_profile.username = Set(username.to_string());
+    target_profile.displayname = Set(Some(displayname.to_string()));
+    target_profile.password = Set(hashed_password.to_string());
+    target_profile.email_address = Set(email_address.to_string());
+    target_profile.profile_picture = Set(Some(profile_picture.to_string()));
 
-    profile::ActiveModel {
-        profile_id: ActiveValue::Set(target_profile.profile_id),
-        username: ActiveValue::Set(username.to_string()),
-        displayname: ActiveValue::Set(Some(displayname.to_string())),
-        password: ActiveValue::Set(hashed_password.to_string()),
-        email_address: ActiveValue::Set(email_address.to_string()),
-        join_datetime: ActiveValue::Set(Local::now().naive_local()),
-        profile_picture: ActiveValue::Set(Some(profile_picture.to_string())),
-    }
-    .update(connection)
-    .await
+    Ok(target_profile.update(connection).await?)
 }
 
-pub async fn get_profile_by_id(
+#[instrument(skip(connection))]
+pub async fn get_profile_by_id<C: ConnectionTrait>(
     profile_id: i32,
-    connection: &DbConn,
-) -> Result<profile::Model, DbErr> {
+    connection: &C,
+) -> Result<profile::Model, Error> {
     let target_profile = profile::Entity::find_by_id(profile_id)
         .one(connection)
         .await?
-        .ok_or(DbErr::Custom(
-            "Couldn't find a profile with the specified identifier.".to_owned(),
-        ));
+        .ok_or(Error::not_found(EntityKind::Profile, profile_id));
 
     return target_profile;
 }
 
+#[instrument(skip(username, connection))]
 pub async fn get_profile_by_username(
     username: &str,
     connection: &DbConn,
-) -> Result<profile::Model, DbErr> {
+) -> Result<profile::Model, Error> {
     let target_profile = profile::Entity::find()
         .filter(profile::Column::Username.eq(username))
         .one(connection)
         .await?
-        .ok_or(DbErr::Custom(
-            "Couldn't find a profile with the specified username.".to_owned(),
-        ));
+        .ok_or(Error::not_found(EntityKind::Profile, username));
 
     return target_profile;
 }
 
-pub async fn delete_profile_by_id(
-    profile_id: i32,
+/// Checks the credentials of a profile. Unknown usernames and wrong passwords are both reported
+/// as [`Error::Forbidden`], failures of the database itself are passed on.
+#[instrument(skip(username, password, password_config, connection))]
+pub async fn authenticate_profile(
+    username: &str,
+    password: &str,
+    password_config: &PasswordConfig,
     connection: &DbConn,
-) -> Result<DeleteResult, DbErr> {
-    let target_profile = profile::Entity::delete_by_id(profile_id)
-        .exec(connection)
-        .await?;
+) -> Result<profile::Model, Error> {
+    let target_profile = get_profile_by_username(username, connection).await;
+
+    let target_profile = match target_profile {
+        Ok(profile)
+            if verify_password(password, &profile.password)
+                || verify_legacy_password(password, &profile.password) =>
+        {
+            profile
+        }
+        Ok(_) => {
+            warn!("R: Failed login attempt for username {:?}", username);
+            return Err(Error::Forbidden("Invalid username or password.".to_owned()));
+        }
+        Err(Error::NotFound { .. }) => {
+            verify_dummy_password(password);
 
-    return Ok(target_profile);
-}
+            warn!("R: Failed login attempt for username {:?}", username);
+            return Err(Error::Forbidden("Invalid username or password.".to_owned()));
+        }
+        Err(err) => return Err(err),
+    };
 
-pub async fn check_profile_exists(
-    profile_id: i32,
-    connection: &DbConn,
-) -> Result<profile::Model, DbErr> {
-    let target_profile = get_profile_by_id(profile_id, connection).await;
+    // plaintext passwords of profiles created before hashing are upgraded here as well
 
-    if target_profile.is_err() {
-        return Err(DbErr::Custom(
-            "Couldn't find a profile with the specified identifier.".to_owned(),
-        ));
+    if !password_needs_rehash(&target_profile.password, password_config) {
+        return Ok(target_profile);
     }
 
-    target_profile
+    let mut updated_profile: profile::ActiveModel = target_profile.into();
+    updated_profile.password = Set(hash_password(password, password_config)?);
+
+    let updated_profile = updated_profile.update(connection).await?;
+    info!(
+        "U: Password hash of profile has been upgraded: {:?}",
+        updated_profile.profile_id
+    );
+
+    Ok(updated_profile)
+}
+
+#[instrument(skip(connection))]
+pub async fn check_profile_exists<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<profile::Model, Error> {
+    get_profile_by_id(profile_id, connection).await
 }
 
 fn is_username_valid(username: &str) -> bool {
-    return !username.contains(char::is_whitespace) && username.len() < 33;
+    !username.contains(char::is_whitespace) && username.len() < 33
 }
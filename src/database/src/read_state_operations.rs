@@ -0,0 +1,139 @@
+use crate::{
+    get_group_message_by_id, get_private_chat_by_id, get_private_message_by_id, EntityKind, Error,
+};
+use entities::*;
+use log::*;
+use sea_orm::*;
+use tracing::instrument;
+
+/// Returns up to which message a participant of a private chat has read it.
+pub fn private_chat_last_read_message_id(
+    private_chat: &private_chat::Model,
+    profile_id: i32,
+) -> Option<i32> {
+    if private_chat.first_profile_id == Some(profile_id) {
+        private_chat.first_last_read_message_id
+    } else if private_chat.second_profile_id == Some(profile_id) {
+        private_chat.second_last_read_message_id
+    } else {
+        None
+    }
+}
+
+/// Advances the read cursor of a group chat member.
+///
+/// Without a message identifier the cursor is moved to the newest message. Cursors never move
+/// backwards, so that clients may report read positions out of order.
+#[instrument(skip(connection))]
+pub async fn mark_group_chat_read(
+    profile_id: i32,
+    group_chat_id: i32,
+    message_id: Option<i32>,
+    connection: &DbConn,
+) -> Result<group_chat_member::Model, Error> {
+    let membership = group_chat_member::Entity::find()
+        .filter(group_chat_member::Column::ProfileId.eq(profile_id))
+        .filter(group_chat_member::Column::GroupChatId.eq(group_chat_id))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::GroupChatMember, profile_id))?;
+
+    let read_message_id = match message_id {
+        Some(message_id) => {
+            let message = get_group_message_by_id(message_id, connection).await?;
+
+            if message.chat_id != group_chat_id {
+                warn!(
+                    "U: Group message with ID {:?} is not part of group chat {:?}",
+                    message_id, group_chat_id
+                );
+                return Err(Error::not_found(EntityKind::GroupChatMessage, message_id));
+            }
+
+            message.message_id
+        }
+        None => match group_chat_message::Entity::find()
+            .filter(group_chat_message::Column::ChatId.eq(group_chat_id))
+            .order_by_desc(group_chat_message::Column::MessageId)
+            .one(connection)
+            .await?
+        {
+            Some(message) => message.message_id,
+            None => return Ok(membership),
+        },
+    };
+
+    if membership.last_read_message_id >= Some(read_message_id) {
+        return Ok(membership);
+    }
+
+    let mut membership: group_chat_member::ActiveModel = membership.into();
+    membership.last_read_message_id = Set(Some(read_message_id));
+
+    Ok(membership.update(connection).await?)
+}
+
+/// Advances the read cursor of a participant of a private chat.
+///
+/// Behaves like [`mark_group_chat_read`].
+#[instrument(skip(connection))]
+pub async fn mark_private_chat_read(
+    profile_id: i32,
+    private_chat_id: i32,
+    message_id: Option<i32>,
+    connection: &DbConn,
+) -> Result<private_chat::Model, Error> {
+    let private_chat = get_private_chat_by_id(private_chat_id, connection).await?;
+
+    if private_chat.first_profile_id != Some(profile_id)
+        && private_chat.second_profile_id != Some(profile_id)
+    {
+        return Err(Error::Forbidden(
+            "Only participants can mark a private chat as read.".to_owned(),
+        ));
+    }
+
+    let read_message_id = match message_id {
+        Some(message_id) => {
+            let message = get_private_message_by_id(message_id, connection).await?;
+
+            if message.private_chat_id != private_chat_id {
+                warn!(
+                    "U: Private message with ID {:?} is not part of private chat {:?}",
+                    message_id, private_chat_id
+                );
+                return Err(Error::not_found(EntityKind::PrivateMessage, message_id));
+            }
+
+            message.private_message_id
+        }
+        None => match private_message::Entity::find()
+            .filter(private_message::Column::PrivateChatId.eq(private_chat_id))
+            .order_by_desc(private_message::Column::PrivateMessageId)
+            .one(connection)
+            .await?
+        {
+            Some(message) => message.private_message_id,
+            None => return Ok(private_chat),
+        },
+    };
+
+    if private_chat_last_read_message_id(&private_chat, profile_id) >= Some(read_message_id) {
+        return Ok(private_chat);
+    }
+
+    let is_first_profile = private_chat.first_profile_id == Some(profile_id);
+    let is_second_profile = private_chat.second_profile_id == Some(profile_id);
+
+    let mut private_chat: private_chat::ActiveModel = private_chat.into();
+
+    // both columns are set for chats of a profile with itself
+    if is_first_profile {
+        private_chat.first_last_read_message_id = Set(Some(read_message_id));
+    }
+    if is_second_profile {
+        private_chat.second_last_read_message_id = Set(Some(read_message_id));
+    }
+
+    Ok(private_chat.update(connection).await?)
+}
@@ -0,0 +1,123 @@
+use crate::{check_profile_exists, EntityKind, Error};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Local};
+use entities::*;
+use log::*;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sea_orm::*;
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+/// Creates a new session for a profile, which expires after `lifetime_hours`.
+///
+/// Returns the plaintext access token together with the stored session. Only a SHA-256
+/// digest of the token is persisted, so the token cannot be recovered from the database.
+#[instrument(skip(connection))]
+pub async fn insert_session(
+    profile_id: i32,
+    lifetime_hours: u32,
+    connection: &DbConn,
+) -> Result<(String, session::Model), Error> {
+    let target_profile = check_profile_exists(profile_id, connection).await?;
+
+    let mut token_bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut token_bytes);
+    let access_token = URL_SAFE_NO_PAD.encode(token_bytes);
+
+    let creation_date = Local::now().naive_local();
+
+    let new_session = session::ActiveModel {
+        profile_id: ActiveValue::Set(target_profile.profile_id),
+        token_hash: ActiveValue::Set(hash_access_token(&access_token)),
+        creation_date: ActiveValue::Set(creation_date),
+        expiration_date: ActiveValue::Set(creation_date + Duration::hours(lifetime_hours.into())),
+        ..Default::default()
+    }
+    .insert(connection)
+    .await;
+
+    match new_session {
+        Ok(session) => {
+            info!(
+                "C: New session has been created for profile: {:?}",
+                session.profile_id
+            );
+            Ok((access_token, session))
+        }
+        Err(err) => {
+            warn!("C: Unable to create a new session: {}", err);
+            Err(err.into())
+        }
+    }
+}
+
+/// Resolves an access token to its session, rejecting unknown and expired tokens.
+#[instrument(skip(access_token, connection))]
+pub async fn get_session_by_token(
+    access_token: &str,
+    connection: &DbConn,
+) -> Result<session::Model, Error> {
+    let token_hash = hash_access_token(access_token);
+
+    // expired sessions are treated like unknown ones
+    let target_session = session::Entity::find()
+        .filter(session::Column::TokenHash.eq(token_hash.as_str()))
+        .filter(session::Column::ExpirationDate.gt(Local::now().naive_local()))
+        .one(connection)
+        .await?
+        .ok_or(Error::not_found(EntityKind::Session, token_hash));
+
+    return target_session;
+}
+
+#[instrument(skip(connection))]
+pub async fn delete_session_by_id(
+    session_id: i32,
+    connection: &DbConn,
+) -> Result<DeleteResult, Error> {
+    let delete_result = session::Entity::delete_by_id(session_id)
+        .exec(connection)
+        .await?;
+
+    return Ok(delete_result);
+}
+
+#[instrument(skip(connection))]
+pub async fn delete_sessions_of_profile<C: ConnectionTrait>(
+    profile_id: i32,
+    connection: &C,
+) -> Result<DeleteResult, Error> {
+    let delete_result = session::Entity::delete_many()
+        .filter(session::Column::ProfileId.eq(profile_id))
+        .exec(connection)
+        .await?;
+
+    return Ok(delete_result);
+}
+
+#[instrument(skip(connection))]
+pub async fn delete_expired_sessions(connection: &DbConn) -> Result<DeleteResult, Error> {
+    let delete_result = session::Entity::delete_many()
+        .filter(session::Column::ExpirationDate.lte(Local::now().naive_local()))
+        .exec(connection)
+        .await?;
+
+    return Ok(delete_result);
+}
+
+/// Counts the sessions which haven't expired yet.
+#[instrument(skip(connection))]
+pub async fn count_active_sessions(connection: &DbConn) -> Result<u64, Error> {
+    let active_sessions = session::Entity::find()
+        .filter(session::Column::ExpirationDate.gt(Local::now().naive_local()))
+        .count(connection)
+        .await?;
+
+    return Ok(active_sessions);
+}
+
+fn hash_access_token(access_token: &str) -> String {
+    format!("{:x}", Sha256::digest(access_token.as_bytes()))
+}
@@ -10,16 +10,37 @@ pub struct Model {
     pub group_chat_id: i32,
     pub creation_date: DateTime,
     pub group_picture: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<i32>,
+    pub updated_at: DateTime,
+    pub approval_required: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::group_chat_invite::Entity")]
+    GroupChatInvite,
+    #[sea_orm(has_many = "super::group_chat_join_request::Entity")]
+    GroupChatJoinRequest,
     #[sea_orm(has_many = "super::group_chat_member::Entity")]
     GroupChatMember,
     #[sea_orm(has_many = "super::group_chat_message::Entity")]
     GroupChatMessage,
 }
 
+impl Related<super::group_chat_invite::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GroupChatInvite.def()
+    }
+}
+
+impl Related<super::group_chat_join_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GroupChatJoinRequest.def()
+    }
+}
+
 impl Related<super::group_chat_member::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::GroupChatMember.def()
@@ -10,6 +10,8 @@ pub struct Model {
     pub member_id: i32,
     pub profile_id: i32,
     pub group_chat_id: i32,
+    pub last_read_message_id: Option<i32>,
+    pub role: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub message_id: i32,
-    pub author_id: i32,
+    pub author_id: Option<i32>,
     pub send_time: DateTime,
     pub content: Option<String>,
     pub chat_id: i32,
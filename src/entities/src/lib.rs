@@ -1,7 +1,12 @@
 pub mod prelude;
 
+pub mod erasure_audit;
 pub mod group_chat;
+pub mod group_chat_invite;
+pub mod group_chat_join_request;
 pub mod group_chat_member;
 pub mod group_chat_message;
+pub mod private_chat;
 pub mod private_message;
 pub mod profile;
+pub mod session;
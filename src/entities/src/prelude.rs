@@ -1,7 +1,12 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
+pub use super::erasure_audit::Entity as ErasureAudit;
 pub use super::group_chat::Entity as GroupChat;
+pub use super::group_chat_invite::Entity as GroupChatInvite;
+pub use super::group_chat_join_request::Entity as GroupChatJoinRequest;
 pub use super::group_chat_member::Entity as GroupChatMember;
 pub use super::group_chat_message::Entity as GroupChatMessage;
+pub use super::private_chat::Entity as PrivateChat;
 pub use super::private_message::Entity as PrivateMessage;
 pub use super::profile::Entity as Profile;
+pub use super::session::Entity as Session;
@@ -8,13 +8,25 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub private_message_id: i32,
-    pub sender_id: i32,
-    pub recipient_id: i32,
+    pub private_chat_id: i32,
+    pub sender_id: Option<i32>,
+    pub recipient_id: Option<i32>,
     pub content: Option<String>,
+    pub sent_at: DateTime,
+    pub edited_at: Option<DateTime>,
+    pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::private_chat::Entity",
+        from = "Column::PrivateChatId",
+        to = "super::private_chat::Column::PrivateChatId",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    PrivateChat,
     #[sea_orm(
         belongs_to = "super::profile::Entity",
         from = "Column::RecipientId",
@@ -33,4 +45,10 @@ pub enum Relation {
     Profile1,
 }
 
+impl Related<super::private_chat::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PrivateChat.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
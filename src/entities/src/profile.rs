@@ -15,14 +15,25 @@ pub struct Model {
     pub email_address: String,
     pub join_datetime: DateTime,
     pub profile_picture: Option<String>,
+    pub is_admin: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::group_chat_join_request::Entity")]
+    GroupChatJoinRequest,
     #[sea_orm(has_many = "super::group_chat_member::Entity")]
     GroupChatMember,
     #[sea_orm(has_many = "super::group_chat_message::Entity")]
     GroupChatMessage,
+    #[sea_orm(has_many = "super::session::Entity")]
+    Session,
+}
+
+impl Related<super::group_chat_join_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GroupChatJoinRequest.def()
+    }
 }
 
 impl Related<super::group_chat_member::Entity> for Entity {
@@ -37,4 +48,10 @@ impl Related<super::group_chat_message::Entity> for Entity {
     }
 }
 
+impl Related<super::session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Session.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,6 +2,17 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20230511_212747_create_initial_tables;
 mod m20230804_181132_add_displayname_to_profile;
+mod m20261018_120000_create_session_table;
+mod m20261018_130000_add_admin_flag_to_profile;
+mod m20261018_150000_add_timestamps_to_private_message;
+mod m20261018_160000_create_private_chat_table;
+mod m20261018_170000_add_read_state;
+mod m20261018_180000_add_profile_erasure;
+mod m20261018_190000_add_group_chat_details;
+mod m20261018_200000_add_group_chat_roles;
+mod m20261018_210000_add_group_chat_invites;
+mod m20261018_220000_add_unique_group_chat_membership;
+mod schema_helpers;
 pub struct Migrator;
 
 #[async_trait::async_trait]
@@ -10,6 +21,16 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20230511_212747_create_initial_tables::Migration),
             Box::new(m20230804_181132_add_displayname_to_profile::Migration),
+            Box::new(m20261018_120000_create_session_table::Migration),
+            Box::new(m20261018_130000_add_admin_flag_to_profile::Migration),
+            Box::new(m20261018_150000_add_timestamps_to_private_message::Migration),
+            Box::new(m20261018_160000_create_private_chat_table::Migration),
+            Box::new(m20261018_170000_add_read_state::Migration),
+            Box::new(m20261018_180000_add_profile_erasure::Migration),
+            Box::new(m20261018_190000_add_group_chat_details::Migration),
+            Box::new(m20261018_200000_add_group_chat_roles::Migration),
+            Box::new(m20261018_210000_add_group_chat_invites::Migration),
+            Box::new(m20261018_220000_add_unique_group_chat_membership::Migration),
         ]
     }
 }
@@ -1,3 +1,4 @@
+use crate::schema_helpers::profile_reference;
 use sea_orm_migration::prelude::*;
 
 #[derive(DeriveMigrationName)]
@@ -6,73 +7,163 @@ pub struct Migration;
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        let db_connection = manager.get_connection();
-
-        db_connection
-            .execute_unprepared(
-                "CREATE TABLE IF NOT EXISTS profile (
-                    profile_id INT AUTO_INCREMENT PRIMARY KEY,
-                    username VARCHAR(32) NOT NULL,
-                    password VARCHAR(1024) NOT NULL,
-                    email_address VARCHAR(128) NOT NULL,
-                    join_datetime DATETIME NOT NULL,
-                    profile_picture VARCHAR(64)
-                )",
+        manager
+            .create_table(
+                Table::create()
+                    .table(Profile::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Profile::ProfileId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Profile::Username).string_len(32).not_null())
+                    .col(
+                        ColumnDef::new(Profile::Password)
+                            .string_len(1024)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Profile::EmailAddress)
+                            .string_len(128)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Profile::JoinDatetime).date_time().not_null())
+                    .col(ColumnDef::new(Profile::ProfilePicture).string_len(64))
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "CREATE TABLE IF NOT EXISTS private_message (
-                    private_message_id INT AUTO_INCREMENT PRIMARY KEY,
-                    sender_id INT NOT NULL,
-                    recipient_id INT NOT NULL,
-                    content VARCHAR(2048),
-                    CONSTRAINT fk_sender
-                        FOREIGN KEY(sender_id) REFERENCES profile(profile_id),
-                    CONSTRAINT fk_recipient
-                        FOREIGN KEY(recipient_id) REFERENCES profile(profile_id)
-                )",
+        manager
+            .create_table(
+                Table::create()
+                    .table(PrivateMessage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PrivateMessage::PrivateMessageId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(&mut profile_reference(manager, PrivateMessage::SenderId))
+                    .col(&mut profile_reference(manager, PrivateMessage::RecipientId))
+                    .col(ColumnDef::new(PrivateMessage::Content).string_len(2048))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sender")
+                            .from(PrivateMessage::Table, PrivateMessage::SenderId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_recipient")
+                            .from(PrivateMessage::Table, PrivateMessage::RecipientId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "CREATE TABLE IF NOT EXISTS group_chat (
-                    group_chat_id INT AUTO_INCREMENT PRIMARY KEY,
-                    creation_date DATETIME NOT NULL,
-                    group_picture VARCHAR(64)
-                )",
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupChat::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupChat::GroupChatId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChat::CreationDate)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GroupChat::GroupPicture).string_len(64))
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "CREATE TABLE IF NOT EXISTS group_chat_message (
-                    message_id INT AUTO_INCREMENT PRIMARY KEY,
-                    author_id INT NOT NULL,
-                    send_time DATETIME NOT NULL,
-                    content VARCHAR(2048),
-                    chat_id INT NOT NULL,
-                    CONSTRAINT fk_message_author
-                        FOREIGN KEY(author_id) REFERENCES profile(profile_id),
-                    CONSTRAINT fk_message_group_chat
-                        FOREIGN KEY(chat_id) REFERENCES group_chat(group_chat_id)
-                )",
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupChatMessage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupChatMessage::MessageId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(&mut profile_reference(manager, GroupChatMessage::AuthorId))
+                    .col(
+                        ColumnDef::new(GroupChatMessage::SendTime)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GroupChatMessage::Content).string_len(2048))
+                    .col(
+                        ColumnDef::new(GroupChatMessage::ChatId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_author")
+                            .from(GroupChatMessage::Table, GroupChatMessage::AuthorId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_group_chat")
+                            .from(GroupChatMessage::Table, GroupChatMessage::ChatId)
+                            .to(GroupChat::Table, GroupChat::GroupChatId),
+                    )
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "CREATE TABLE IF NOT EXISTS group_chat_member (
-                    member_id INT AUTO_INCREMENT PRIMARY KEY,
-                    profile_id INT NOT NULL,
-                    group_chat_id INT NOT NULL,
-                    CONSTRAINT fk_profile
-                        FOREIGN KEY(profile_id) REFERENCES profile(profile_id),
-                    CONSTRAINT fk_member_group_chat
-                        FOREIGN KEY(group_chat_id) REFERENCES group_chat(group_chat_id)
-                )",
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupChatMember::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupChatMember::MemberId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatMember::ProfileId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatMember::GroupChatId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_profile")
+                            .from(GroupChatMember::Table, GroupChatMember::ProfileId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_member_group_chat")
+                            .from(GroupChatMember::Table, GroupChatMember::GroupChatId)
+                            .to(GroupChat::Table, GroupChat::GroupChatId),
+                    )
+                    .to_owned(),
             )
             .await?;
 
@@ -80,28 +171,90 @@ impl MigrationTrait for Migration {
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        let db_connection = manager.get_connection();
-
-        db_connection
-            .execute_unprepared("DROP TABLE IF EXISTS group_chat_member")
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(GroupChatMember::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
             .await?;
 
-        db_connection
-            .execute_unprepared("DROP TABLE IF EXISTS group_chat_message")
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(GroupChatMessage::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
             .await?;
 
-        db_connection
-            .execute_unprepared("DROP TABLE IF EXISTS private_message")
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PrivateMessage::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
             .await?;
 
-        db_connection
-            .execute_unprepared("DROP TABLE IF EXISTS group_chat")
+        manager
+            .drop_table(Table::drop().table(GroupChat::Table).if_exists().to_owned())
             .await?;
 
-        db_connection
-            .execute_unprepared("DROP TABLE IF EXISTS profile")
+        manager
+            .drop_table(Table::drop().table(Profile::Table).if_exists().to_owned())
             .await?;
 
         Ok(())
     }
 }
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum Profile {
+    Table,
+    ProfileId,
+    Username,
+    Password,
+    EmailAddress,
+    JoinDatetime,
+    ProfilePicture,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum PrivateMessage {
+    Table,
+    PrivateMessageId,
+    SenderId,
+    RecipientId,
+    Content,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum GroupChat {
+    Table,
+    GroupChatId,
+    CreationDate,
+    GroupPicture,
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMessage {
+    Table,
+    MessageId,
+    AuthorId,
+    SendTime,
+    Content,
+    ChatId,
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMember {
+    Table,
+    MemberId,
+    ProfileId,
+    GroupChatId,
+}
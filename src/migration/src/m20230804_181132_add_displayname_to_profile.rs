@@ -1,3 +1,4 @@
+use crate::schema_helpers::is_sqlite;
 use sea_orm_migration::prelude::*;
 
 #[derive(DeriveMigrationName)]
@@ -6,27 +7,35 @@ pub struct Migration;
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        let db_connection = manager.get_connection();
-
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    ADD displayname varchar(32)
-                        AFTER username",
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .add_column(ColumnDef::new(Profile::Displayname).string_len(32))
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    MODIFY username varchar(32) NOT NULL",
-            )
-            .await?;
+        // usernames are created as NOT NULL on SQLite already
+        if !is_sqlite(manager) {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Profile::Table)
+                        .modify_column(ColumnDef::new(Profile::Username).string_len(32).not_null())
+                        .to_owned(),
+                )
+                .await?;
+        }
 
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    ADD CONSTRAINT unique_username UNIQUE(username)",
+        manager
+            .create_index(
+                Index::create()
+                    .name("unique_username")
+                    .table(Profile::Table)
+                    .col(Profile::Username)
+                    .unique()
+                    .to_owned(),
             )
             .await?;
 
@@ -34,29 +43,42 @@ impl MigrationTrait for Migration {
     }
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        let db_connection = manager.get_connection();
-
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    DROP COLUMN IF EXISTS displayname",
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .drop_column(Profile::Displayname)
+                    .to_owned(),
             )
             .await?;
 
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    MODIFY username varchar(32) NULL",
-            )
-            .await?;
+        if !is_sqlite(manager) {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Profile::Table)
+                        .modify_column(ColumnDef::new(Profile::Username).string_len(32).null())
+                        .to_owned(),
+                )
+                .await?;
+        }
 
-        db_connection
-            .execute_unprepared(
-                "ALTER TABLE profile 
-                    DROP CONSTRAINT IF EXISTS unique_username",
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("unique_username")
+                    .table(Profile::Table)
+                    .to_owned(),
             )
             .await?;
 
         Ok(())
     }
 }
+
+#[derive(DeriveIden)]
+enum Profile {
+    Table,
+    Username,
+    Displayname,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Session::SessionId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Session::ProfileId).integer().not_null())
+                    .col(ColumnDef::new(Session::TokenHash).char_len(64).not_null())
+                    .col(ColumnDef::new(Session::CreationDate).date_time().not_null())
+                    .col(
+                        ColumnDef::new(Session::ExpirationDate)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("unique_token_hash")
+                            .col(Session::TokenHash)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_profile")
+                            .from(Session::Table, Session::ProfileId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Session::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    SessionId,
+    ProfileId,
+    TokenHash,
+    CreationDate,
+    ExpirationDate,
+}
+
+#[derive(DeriveIden)]
+enum Profile {
+    Table,
+    ProfileId,
+}
@@ -0,0 +1,77 @@
+use crate::schema_helpers::is_sqlite;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // existing messages have no known send time, they are stamped with the migration time.
+        // SQLite only accepts constant defaults for added columns, its databases are always fresh.
+        let mut sent_at = ColumnDef::new(PrivateMessage::SentAt);
+        sent_at.date_time().not_null();
+
+        if is_sqlite(manager) {
+            sent_at.default("1970-01-01 00:00:00");
+        } else {
+            sent_at.default(Expr::current_timestamp());
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .add_column(&mut sent_at)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .add_column(ColumnDef::new(PrivateMessage::EditedAt).date_time())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .add_column(ColumnDef::new(PrivateMessage::DeletedAt).date_time())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            PrivateMessage::DeletedAt,
+            PrivateMessage::EditedAt,
+            PrivateMessage::SentAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(PrivateMessage::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PrivateMessage {
+    Table,
+    SentAt,
+    EditedAt,
+    DeletedAt,
+}
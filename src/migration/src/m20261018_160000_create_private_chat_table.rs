@@ -0,0 +1,236 @@
+use crate::schema_helpers::{is_sqlite, profile_reference};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // the participants are stored ordered, so that every pair maps to exactly one chat
+        manager
+            .create_table(
+                Table::create()
+                    .table(PrivateChat::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PrivateChat::PrivateChatId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(&mut profile_reference(manager, PrivateChat::FirstProfileId))
+                    .col(&mut profile_reference(
+                        manager,
+                        PrivateChat::SecondProfileId,
+                    ))
+                    .col(
+                        ColumnDef::new(PrivateChat::CreationDate)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("unique_participants")
+                            .col(PrivateChat::FirstProfileId)
+                            .col(PrivateChat::SecondProfileId)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_first_participant")
+                            .from(PrivateChat::Table, PrivateChat::FirstProfileId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_second_participant")
+                            .from(PrivateChat::Table, PrivateChat::SecondProfileId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let existing_chats = Query::select()
+            .expr(first_participant())
+            .expr(second_participant())
+            .expr(Expr::col(PrivateMessage::SentAt).min())
+            .from(PrivateMessage::Table)
+            .add_group_by([first_participant(), second_participant()])
+            .to_owned();
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(PrivateChat::Table)
+                    .columns([
+                        PrivateChat::FirstProfileId,
+                        PrivateChat::SecondProfileId,
+                        PrivateChat::CreationDate,
+                    ])
+                    .select_from(existing_chats)
+                    .map_err(|err| DbErr::Migration(err.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        // SQLite can't add foreign keys to existing tables, except as part of a new column
+        let mut private_chat_id = ColumnDef::new(PrivateMessage::PrivateChatId);
+        private_chat_id.integer();
+
+        if is_sqlite(manager) {
+            private_chat_id.extra("REFERENCES private_chat(private_chat_id)");
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .add_column(&mut private_chat_id)
+                    .to_owned(),
+            )
+            .await?;
+
+        let private_chat_of_message = Query::select()
+            .column(PrivateChat::PrivateChatId)
+            .from(PrivateChat::Table)
+            .and_where(
+                Expr::col((PrivateChat::Table, PrivateChat::FirstProfileId))
+                    .eq(first_participant()),
+            )
+            .and_where(
+                Expr::col((PrivateChat::Table, PrivateChat::SecondProfileId))
+                    .eq(second_participant()),
+            )
+            .to_owned();
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(PrivateMessage::Table)
+                    .value(
+                        PrivateMessage::PrivateChatId,
+                        SimpleExpr::SubQuery(
+                            None,
+                            Box::new(private_chat_of_message.into_sub_query_statement()),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        if is_sqlite(manager) {
+            return Ok(());
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .modify_column(
+                        ColumnDef::new(PrivateMessage::PrivateChatId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_private_chat")
+                    .from(PrivateMessage::Table, PrivateMessage::PrivateChatId)
+                    .to(PrivateChat::Table, PrivateChat::PrivateChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if is_sqlite(manager) {
+            return Err(DbErr::Migration(
+                "Private chats can't be rolled back on SQLite, recreate the database instead."
+                    .to_owned(),
+            ));
+        }
+
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_private_chat")
+                    .table(PrivateMessage::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PrivateMessage::Table)
+                    .drop_column(PrivateMessage::PrivateChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PrivateChat::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Lower profile identifier of the participants of a private message.
+fn first_participant() -> SimpleExpr {
+    let sender_id = Expr::col((PrivateMessage::Table, PrivateMessage::SenderId));
+    let recipient_id = Expr::col((PrivateMessage::Table, PrivateMessage::RecipientId));
+
+    Expr::case(sender_id.clone().lt(recipient_id.clone()), sender_id)
+        .finally(recipient_id)
+        .into()
+}
+
+/// Higher profile identifier of the participants of a private message.
+fn second_participant() -> SimpleExpr {
+    let sender_id = Expr::col((PrivateMessage::Table, PrivateMessage::SenderId));
+    let recipient_id = Expr::col((PrivateMessage::Table, PrivateMessage::RecipientId));
+
+    Expr::case(sender_id.clone().lt(recipient_id.clone()), recipient_id)
+        .finally(sender_id)
+        .into()
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum PrivateChat {
+    Table,
+    PrivateChatId,
+    FirstProfileId,
+    SecondProfileId,
+    CreationDate,
+}
+
+#[derive(DeriveIden)]
+enum PrivateMessage {
+    Table,
+    PrivateChatId,
+    SenderId,
+    RecipientId,
+    SentAt,
+}
+
+#[derive(DeriveIden)]
+enum Profile {
+    Table,
+    ProfileId,
+}
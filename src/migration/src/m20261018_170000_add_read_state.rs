@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // no foreign keys, messages may be removed after they have been read
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChatMember::Table)
+                    .add_column(ColumnDef::new(GroupChatMember::LastReadMessageId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        for column in [
+            PrivateChat::FirstLastReadMessageId,
+            PrivateChat::SecondLastReadMessageId,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(PrivateChat::Table)
+                        .add_column(ColumnDef::new(column).integer())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            PrivateChat::FirstLastReadMessageId,
+            PrivateChat::SecondLastReadMessageId,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(PrivateChat::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChatMember::Table)
+                    .drop_column(GroupChatMember::LastReadMessageId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMember {
+    Table,
+    LastReadMessageId,
+}
+
+#[derive(DeriveIden)]
+enum PrivateChat {
+    Table,
+    FirstLastReadMessageId,
+    SecondLastReadMessageId,
+}
@@ -0,0 +1,205 @@
+use crate::schema_helpers::is_sqlite;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // references to erased profiles are set to NULL when their messages are anonymized. On
+        // SQLite these columns have been created nullable already.
+        if !is_sqlite(manager) {
+            for (table, column) in profile_references() {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table)
+                            .modify_column(ColumnDef::new(column).integer().null())
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        // no foreign keys, the audit entries outlive the erased profiles
+        manager
+            .create_table(
+                Table::create()
+                    .table(ErasureAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ErasureAudit::ErasureAuditId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ErasureAudit::ProfileId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ErasureAudit::RequestedBy)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::Policy)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::ErasedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::RemovedMemberships)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::AffectedGroupMessages)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::AffectedPrivateMessages)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ErasureAudit::RevokedSessions)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ErasureAudit::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        if is_sqlite(manager) {
+            return Ok(());
+        }
+
+        // anonymized rows can't reference a profile anymore and have to be removed first
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(PrivateMessage::Table)
+                    .cond_where(
+                        Cond::any()
+                            .add(Expr::col(PrivateMessage::SenderId).is_null())
+                            .add(Expr::col(PrivateMessage::RecipientId).is_null()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(PrivateChat::Table)
+                    .cond_where(
+                        Cond::any()
+                            .add(Expr::col(PrivateChat::FirstProfileId).is_null())
+                            .add(Expr::col(PrivateChat::SecondProfileId).is_null()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(GroupChatMessage::Table)
+                    .and_where(Expr::col(GroupChatMessage::AuthorId).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        for (table, column) in profile_references().into_iter().rev() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .modify_column(ColumnDef::new(column).integer().not_null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Columns referencing profiles, which are cleared when a profile is erased.
+fn profile_references() -> Vec<(DynIden, DynIden)> {
+    vec![
+        (
+            GroupChatMessage::Table.into_iden(),
+            GroupChatMessage::AuthorId.into_iden(),
+        ),
+        (
+            PrivateMessage::Table.into_iden(),
+            PrivateMessage::SenderId.into_iden(),
+        ),
+        (
+            PrivateMessage::Table.into_iden(),
+            PrivateMessage::RecipientId.into_iden(),
+        ),
+        (
+            PrivateChat::Table.into_iden(),
+            PrivateChat::FirstProfileId.into_iden(),
+        ),
+        (
+            PrivateChat::Table.into_iden(),
+            PrivateChat::SecondProfileId.into_iden(),
+        ),
+    ]
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMessage {
+    Table,
+    AuthorId,
+}
+
+#[derive(DeriveIden)]
+enum PrivateMessage {
+    Table,
+    SenderId,
+    RecipientId,
+}
+
+#[derive(DeriveIden)]
+enum PrivateChat {
+    Table,
+    FirstProfileId,
+    SecondProfileId,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum ErasureAudit {
+    Table,
+    ErasureAuditId,
+    ProfileId,
+    RequestedBy,
+    Policy,
+    ErasedAt,
+    RemovedMemberships,
+    AffectedGroupMessages,
+    AffectedPrivateMessages,
+    RevokedSessions,
+}
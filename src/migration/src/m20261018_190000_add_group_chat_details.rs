@@ -0,0 +1,105 @@
+use crate::schema_helpers::is_sqlite;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // existing group chats keep an empty name until one of their members sets it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .add_column(
+                        ColumnDef::new(GroupChat::Name)
+                            .string_len(64)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .add_column(ColumnDef::new(GroupChat::Description).string_len(1024))
+                    .to_owned(),
+            )
+            .await?;
+
+        // no foreign key, the column is cleared when the creator is erased
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .add_column(ColumnDef::new(GroupChat::CreatedBy).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .add_column(ColumnDef::new(GroupChat::UpdatedAt).date_time())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(GroupChat::Table)
+                    .value(GroupChat::UpdatedAt, Expr::col(GroupChat::CreationDate))
+                    .to_owned(),
+            )
+            .await?;
+
+        if !is_sqlite(manager) {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(GroupChat::Table)
+                        .modify_column(ColumnDef::new(GroupChat::UpdatedAt).date_time().not_null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            GroupChat::UpdatedAt,
+            GroupChat::CreatedBy,
+            GroupChat::Description,
+            GroupChat::Name,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(GroupChat::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupChat {
+    Table,
+    CreationDate,
+    Name,
+    Description,
+    CreatedBy,
+    UpdatedAt,
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChatMember::Table)
+                    .add_column(
+                        ColumnDef::new(GroupChatMember::Role)
+                            .string_len(16)
+                            .not_null()
+                            .default("member"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the earliest membership of every group chat is the one of its creator, as long as the
+        // creator hasn't left. MySQL can't update a table it selects from, unless the selection
+        // is wrapped in a derived table.
+        let first_members = Query::select()
+            .expr_as(
+                Expr::col(GroupChatMember::MemberId).min(),
+                GroupChatMember::MemberId,
+            )
+            .from(GroupChatMember::Table)
+            .group_by_col(GroupChatMember::GroupChatId)
+            .to_owned();
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(GroupChatMember::Table)
+                    .value(GroupChatMember::Role, "owner")
+                    .and_where(
+                        Expr::col(GroupChatMember::MemberId).in_subquery(
+                            Query::select()
+                                .column(GroupChatMember::MemberId)
+                                .from_subquery(first_members, FirstMembers::Table)
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChatMember::Table)
+                    .drop_column(GroupChatMember::Role)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMember {
+    Table,
+    MemberId,
+    GroupChatId,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum FirstMembers {
+    Table,
+}
@@ -0,0 +1,209 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .add_column(
+                        ColumnDef::new(GroupChat::ApprovalRequired)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // no foreign key on the creator, the column is cleared when the creator is erased
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupChatInvite::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupChatInvite::InviteId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatInvite::GroupChatId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatInvite::Code)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GroupChatInvite::CreatedBy).integer())
+                    .col(
+                        ColumnDef::new(GroupChatInvite::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GroupChatInvite::ExpiresAt).date_time())
+                    .col(ColumnDef::new(GroupChatInvite::MaxUses).integer())
+                    .col(
+                        ColumnDef::new(GroupChatInvite::Uses)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(GroupChatInvite::RevokedAt).date_time())
+                    .index(
+                        Index::create()
+                            .name("unique_invite_code")
+                            .col(GroupChatInvite::Code)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_invite_group_chat")
+                            .from(GroupChatInvite::Table, GroupChatInvite::GroupChatId)
+                            .to(GroupChat::Table, GroupChat::GroupChatId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // a profile has at most one pending request per group chat
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupChatJoinRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupChatJoinRequest::JoinRequestId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatJoinRequest::GroupChatId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(GroupChatJoinRequest::ProfileId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GroupChatJoinRequest::InviteId).integer())
+                    .col(
+                        ColumnDef::new(GroupChatJoinRequest::CreatedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .name("unique_join_request")
+                            .col(GroupChatJoinRequest::GroupChatId)
+                            .col(GroupChatJoinRequest::ProfileId)
+                            .unique(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_request_group_chat")
+                            .from(
+                                GroupChatJoinRequest::Table,
+                                GroupChatJoinRequest::GroupChatId,
+                            )
+                            .to(GroupChat::Table, GroupChat::GroupChatId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_request_profile")
+                            .from(GroupChatJoinRequest::Table, GroupChatJoinRequest::ProfileId)
+                            .to(Profile::Table, Profile::ProfileId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_request_invite")
+                            .from(GroupChatJoinRequest::Table, GroupChatJoinRequest::InviteId)
+                            .to(GroupChatInvite::Table, GroupChatInvite::InviteId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(GroupChatJoinRequest::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(GroupChatInvite::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GroupChat::Table)
+                    .drop_column(GroupChat::ApprovalRequired)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(DeriveIden)]
+enum GroupChat {
+    Table,
+    GroupChatId,
+    ApprovalRequired,
+}
+
+#[derive(DeriveIden)]
+enum GroupChatInvite {
+    Table,
+    InviteId,
+    GroupChatId,
+    Code,
+    CreatedBy,
+    CreatedAt,
+    ExpiresAt,
+    MaxUses,
+    Uses,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum GroupChatJoinRequest {
+    Table,
+    JoinRequestId,
+    GroupChatId,
+    ProfileId,
+    InviteId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Profile {
+    Table,
+    ProfileId,
+}
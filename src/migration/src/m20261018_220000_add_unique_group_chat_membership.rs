@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // only the earliest of duplicate memberships is kept. MySQL can't delete from a table it
+        // selects from, unless the selection is wrapped in a derived table.
+        let first_memberships = Query::select()
+            .expr_as(
+                Expr::col(GroupChatMember::MemberId).min(),
+                GroupChatMember::MemberId,
+            )
+            .from(GroupChatMember::Table)
+            .group_by_col(GroupChatMember::ProfileId)
+            .group_by_col(GroupChatMember::GroupChatId)
+            .to_owned();
+
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(GroupChatMember::Table)
+                    .and_where(
+                        Expr::col(GroupChatMember::MemberId).not_in_subquery(
+                            Query::select()
+                                .column(GroupChatMember::MemberId)
+                                .from_subquery(first_memberships, FirstMemberships::Table)
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("unique_group_chat_membership")
+                    .table(GroupChatMember::Table)
+                    .col(GroupChatMember::ProfileId)
+                    .col(GroupChatMember::GroupChatId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("unique_group_chat_membership")
+                    .table(GroupChatMember::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupChatMember {
+    Table,
+    MemberId,
+    ProfileId,
+    GroupChatId,
+}
+
+#[derive(DeriveIden)]
+enum FirstMemberships {
+    Table,
+}
@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DbBackend;
+
+/// SQLite can neither modify columns nor foreign keys of existing tables.
+pub(crate) fn is_sqlite(manager: &SchemaManager) -> bool {
+    manager.get_database_backend() == DbBackend::Sqlite
+}
+
+/// Column referencing a profile, which is cleared once the profile has been erased.
+///
+/// The column only becomes nullable in a later migration, except on SQLite where this can't be
+/// changed afterwards.
+pub(crate) fn profile_reference<T: IntoIden>(manager: &SchemaManager, column: T) -> ColumnDef {
+    let mut column = ColumnDef::new(column);
+    column.integer();
+
+    if is_sqlite(manager) {
+        column.null();
+    } else {
+        column.not_null();
+    }
+
+    column
+}
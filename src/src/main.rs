@@ -1,38 +1,233 @@
-use database::connect_to_database;
+use api::config::Config;
+use clap::{Args, Parser, Subcommand};
+use database::{connect_to_database, DatabaseConfig};
 use futures::executor::block_on;
+use log::LevelFilter;
 use migration::{Migrator, MigratorTrait, SchemaManager};
-use sea_orm::DbErr;
+use sea_orm::{DatabaseConnection, DbErr};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-const BASE_TABLES: [&'static str; 5] = [
+const BASE_TABLES: [&str; 10] = [
     "profile",
+    "private_chat",
     "private_message",
     "group_chat",
     "group_chat_message",
     "group_chat_member",
+    "session",
+    "erasure_audit",
+    "group_chat_invite",
+    "group_chat_join_request",
 ];
 
-fn main() {
-    let migration_result = block_on(run_migrations());
+#[derive(Parser)]
+#[command(name = "sorume-server", version, about)]
+struct Cli {
+    #[command(flatten)]
+    settings: Settings,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Flags which take precedence over the configuration file and the environment
+#[derive(Args)]
+struct Settings {
+    /// Configuration file [default: $SORUME_CONFIG or sorume.toml if it exists]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Address the server listens on
+    #[arg(long, global = true)]
+    bind_address: Option<IpAddr>,
+
+    /// Port the server listens on
+    #[arg(long, global = true)]
+    port: Option<u16>,
+
+    /// Number of worker threads
+    #[arg(long, global = true)]
+    workers: Option<usize>,
+
+    /// URL of the database
+    #[arg(long, global = true)]
+    database_url: Option<String>,
+
+    /// File the log is written to
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Level of the log file
+    #[arg(long, global = true)]
+    log_level: Option<LevelFilter>,
+}
+
+impl Settings {
+    fn load_config(self) -> Result<Config, String> {
+        let mut config = Config::load(self.config.as_deref()).map_err(|err| err.to_string())?;
+
+        if let Some(bind_address) = self.bind_address {
+            config.server.bind_address = bind_address;
+        }
+        if let Some(port) = self.port {
+            config.server.port = port;
+        }
+        if let Some(workers) = self.workers {
+            config.server.workers = Some(workers);
+        }
+        if let Some(database_url) = self.database_url {
+            config.database.url = database_url;
+        }
+        if let Some(log_file) = self.log_file {
+            config.log.file = log_file;
+        }
+        if let Some(log_level) = self.log_level {
+            config.log.file_level = log_level;
+        }
+
+        config.validate().map_err(|err| err.to_string())?;
+
+        Ok(config)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply pending migrations and start the API server (default)
+    Serve,
+    /// Manage the database schema
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+
+        /// Allow actions which drop tables or roll back migrations
+        #[arg(long, global = true)]
+        allow_destructive: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateAction {
+    /// Apply pending migrations
+    Up {
+        /// Number of pending migrations to apply
+        #[arg(short, long)]
+        num: Option<u32>,
+    },
+    /// Roll back applied migrations (destructive)
+    Down {
+        /// Number of applied migrations to roll back
+        #[arg(short, long, default_value_t = 1)]
+        num: u32,
+    },
+    /// Show the status of all migrations
+    Status,
+    /// Drop all tables and reapply all migrations (destructive)
+    Fresh,
+    /// Roll back all migrations and reapply them (destructive)
+    Refresh,
+}
+
+impl MigrateAction {
+    fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            MigrateAction::Down { .. } | MigrateAction::Fresh | MigrateAction::Refresh
+        )
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let config = match cli.settings.load_config() {
+        Ok(config) => config,
+        Err(err) => {
+            println!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config),
+        Command::Migrate {
+            action,
+            allow_destructive,
+        } => {
+            if action.is_destructive() && !allow_destructive {
+                println!("This migration action deletes data. Pass --allow-destructive to run it.");
+                return ExitCode::FAILURE;
+            }
+
+            match block_on(run_migrate_action(&config.database, action)) {
+                Ok(_) => ExitCode::SUCCESS,
+                Err(err) => {
+                    println!("Migrations failed: {err}");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+    }
+}
+
+fn serve(config: Config) -> ExitCode {
+    let migration_result = block_on(run_migrations(&config.database));
 
     if let Some(err) = migration_result.err() {
         println!("Migrations failed: {err}");
+        return ExitCode::FAILURE;
     }
 
-    let api_result = api::run();
+    let api_result = api::run(config);
 
     if let Some(err) = api_result.err() {
         println!("API failed: {err}");
+        return ExitCode::FAILURE;
     }
+
+    ExitCode::SUCCESS
+}
+
+async fn run_migrations(database_config: &DatabaseConfig) -> Result<(), DbErr> {
+    let db_connection = connect_to_database(database_config).await?;
+
+    Migrator::up(&db_connection, None).await?;
+
+    check_base_tables(&db_connection).await
 }
 
-async fn run_migrations() -> Result<(), DbErr> {
-    let db_connection = connect_to_database(true).await?;
+async fn run_migrate_action(
+    database_config: &DatabaseConfig,
+    action: MigrateAction,
+) -> Result<(), DbErr> {
+    let db_connection = connect_to_database(database_config).await?;
+
+    match action {
+        MigrateAction::Up { num } => Migrator::up(&db_connection, num).await?,
+        MigrateAction::Down { num } => Migrator::down(&db_connection, Some(num)).await?,
+        MigrateAction::Status => {
+            for migration in Migrator::get_migration_with_status(&db_connection).await? {
+                println!("{}: {}", migration.name(), migration.status());
+            }
+        }
+        MigrateAction::Fresh => Migrator::fresh(&db_connection).await?,
+        MigrateAction::Refresh => Migrator::refresh(&db_connection).await?,
+    }
+
+    Ok(())
+}
 
-    let schema_manager = SchemaManager::new(&db_connection);
-    Migrator::refresh(&db_connection).await?;
+async fn check_base_tables(db_connection: &DatabaseConnection) -> Result<(), DbErr> {
+    let schema_manager = SchemaManager::new(db_connection);
 
     for base_table in BASE_TABLES {
-        assert!(schema_manager.has_table(base_table).await?);
+        if !schema_manager.has_table(base_table).await? {
+            return Err(DbErr::Custom(format!(
+                "Table {base_table} is missing after running migrations."
+            )));
+        }
     }
 
     Ok(())